@@ -0,0 +1,196 @@
+//! Compares the algorithms running over the dense storage of the graph
+//! with the same algorithms over the previous layout, maps of
+//! `NodeId` sets that hash an id on every step.
+//!
+//! Run with `cargo bench --bench graph_index`.
+use orbweaver::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const LAYERS: usize = 200;
+const WIDTH: usize = 250;
+const EDGES_PER_NODE: usize = 4;
+const RUNS: usize = 5;
+
+/// Layered DAG where every node points to a few nodes of the next
+/// layer, chosen by a fixed xorshift sequence
+fn layered_graph() -> DirectedGraph<()> {
+    let mut graph = DirectedGraph::new();
+    let mut state = 0x9e3779b97f4a7c15u64;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize
+    };
+    for layer in 0..LAYERS {
+        for i in 0..WIDTH {
+            let _ = graph.add_node(format!("{layer}-{i}"), ());
+        }
+    }
+    for layer in 0..LAYERS - 1 {
+        for i in 0..WIDTH {
+            for _ in 0..EDGES_PER_NODE {
+                let to = format!("{}-{}", layer + 1, next() % WIDTH);
+                let from = format!("{layer}-{i}");
+                if !graph.edge_exists(&from, &to) {
+                    let _ = graph.add_edge(from, to);
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// Children and parents of every node stored as in earlier versions
+struct HashedGraph {
+    children: HashMap<NodeId, HashSet<NodeId>>,
+    parents: HashMap<NodeId, HashSet<NodeId>>,
+}
+
+impl HashedGraph {
+    fn new(dg: &DirectedGraph<()>) -> Self {
+        let rows = |neighbors: fn(&DirectedGraph<()>, &NodeId) -> Vec<NodeId>| {
+            dg.node_ids()
+                .map(|node| {
+                    let row = neighbors(dg, &node).into_iter().collect();
+                    (node, row)
+                })
+                .collect()
+        };
+        HashedGraph {
+            children: rows(|dg, node| dg.children(node).unwrap().cloned().collect()),
+            parents: rows(|dg, node| dg.parents(node).unwrap().cloned().collect()),
+        }
+    }
+}
+
+fn hashed_topological_sort(dg: &HashedGraph) -> Vec<NodeId> {
+    let mut remaining = dg
+        .children
+        .iter()
+        .map(|(node, children)| (node, children.len()))
+        .collect::<HashMap<_, _>>();
+    let mut no_deps = remaining
+        .iter()
+        .filter(|(_, &children)| children == 0)
+        .map(|(&node, _)| node)
+        .collect::<Vec<_>>();
+    let mut res = Vec::new();
+    while let Some(node) = no_deps.pop() {
+        res.push(node.clone());
+        for parent in &dg.parents[node] {
+            let children = remaining.get_mut(parent).unwrap();
+            *children -= 1;
+            if *children == 0 {
+                no_deps.push(parent);
+            }
+        }
+    }
+    res
+}
+
+fn hashed_find_path(dg: &HashedGraph, from: &str, to: &str) -> Option<usize> {
+    let start = dg.children.get_key_value(from).unwrap().0.clone();
+    let mut queue = std::collections::VecDeque::from([(start.clone(), 1)]);
+    let mut visited = HashSet::from([start]);
+    while let Some((current, len)) = queue.pop_front() {
+        for child in &dg.children[&current] {
+            if visited.insert(child.clone()) {
+                if child.as_ref() == to {
+                    return Some(len + 1);
+                }
+                queue.push_back((child.clone(), len + 1));
+            }
+        }
+    }
+    None
+}
+
+fn hashed_get_leaves_under(dg: &HashedGraph, nodes: &[&str]) -> Vec<NodeId> {
+    let mut leaves = Vec::new();
+    let mut visited = HashSet::new();
+    let mut to_visit = nodes
+        .iter()
+        .map(|node| dg.children.get_key_value(*node).unwrap().0.clone())
+        .collect::<Vec<_>>();
+    while let Some(node) = to_visit.pop() {
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+        let children = &dg.children[&node];
+        if children.is_empty() {
+            leaves.push(node);
+            continue;
+        }
+        to_visit.extend(children.iter().cloned());
+    }
+    leaves
+}
+
+/// Fastest of `RUNS` runs of `f`, each given a fresh value from the
+/// untimed `setup`
+fn time<S, T>(mut setup: impl FnMut() -> S, mut f: impl FnMut(S) -> T) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let input = setup();
+            let start = Instant::now();
+            black_box(f(input));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, indexed: Duration, hashed: Duration) {
+    println!(
+        "{name:<24} indexed {:>10.3?}   hashed {:>10.3?}   speedup {:>5.1}x",
+        indexed,
+        hashed,
+        hashed.as_secs_f64() / indexed.as_secs_f64()
+    );
+}
+
+fn main() {
+    let graph = layered_graph();
+    let hashed = HashedGraph::new(&graph);
+    println!(
+        "{} nodes, {} edges, best of {RUNS} runs",
+        graph.n_nodes(),
+        graph.n_edges()
+    );
+
+    report(
+        "topological_sort",
+        time(
+            || (),
+            |_| orbweaver::algo::topological_sort(&graph).unwrap(),
+        ),
+        time(|| (), |_| hashed_topological_sort(&hashed)),
+    );
+
+    let from = "0-0";
+    let to = format!("{}-{}", LAYERS - 1, WIDTH - 1);
+    assert_eq!(
+        graph.find_path(from, &to).unwrap().map(|path| path.len()),
+        hashed_find_path(&hashed, from, &to)
+    );
+    report(
+        "find_path",
+        time(|| (), |_| graph.find_path(from, &to).unwrap()),
+        time(|| (), |_| hashed_find_path(&hashed, from, &to)),
+    );
+
+    let roots = ["0-0", "0-1", "0-2", "50-0", "100-0"];
+    let mut indexed_leaves = graph.get_leaves_under(&roots).unwrap();
+    let mut hashed_leaves = hashed_get_leaves_under(&hashed, &roots);
+    indexed_leaves.sort_unstable();
+    hashed_leaves.sort_unstable();
+    assert_eq!(indexed_leaves, hashed_leaves);
+    report(
+        "get_leaves_under",
+        time(|| (), |_| graph.get_leaves_under(&roots).unwrap()),
+        time(|| (), |_| hashed_get_leaves_under(&hashed, &roots)),
+    );
+}
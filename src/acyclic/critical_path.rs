@@ -0,0 +1,197 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::ops::{Add, Sub};
+
+/// Scheduling information for a single node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeSchedule<W> {
+    earliest_start: W,
+    latest_start: W,
+    duration: W,
+}
+
+impl<W> NodeSchedule<W>
+where
+    W: Copy + Add<Output = W> + Sub<Output = W>,
+{
+    pub fn earliest_start(&self) -> W {
+        self.earliest_start
+    }
+    pub fn earliest_finish(&self) -> W {
+        self.earliest_start + self.duration
+    }
+    pub fn latest_start(&self) -> W {
+        self.latest_start
+    }
+    pub fn latest_finish(&self) -> W {
+        self.latest_start + self.duration
+    }
+    pub fn duration(&self) -> W {
+        self.duration
+    }
+    /// How much the node can be delayed without delaying the project
+    pub fn slack(&self) -> W {
+        self.latest_start - self.earliest_start
+    }
+}
+
+/// Result of [`DirectedAcyclicGraph::critical_path`]
+#[derive(Debug, Clone)]
+pub struct CriticalPath<W> {
+    path: Vec<NodeId>,
+    duration: W,
+    schedule: HashMap<NodeId, NodeSchedule<W>>,
+}
+
+impl<W> CriticalPath<W> {
+    /// Nodes on the critical path, from the first to the last to run
+    pub fn path(&self) -> &[NodeId] {
+        &self.path
+    }
+    /// Total duration of the whole graph
+    pub fn duration(&self) -> &W {
+        &self.duration
+    }
+    pub fn schedule(&self, id: impl AsRef<str>) -> Option<&NodeSchedule<W>> {
+        self.schedule.get(id.as_ref())
+    }
+    pub fn schedules(&self) -> impl Iterator<Item = (&NodeId, &NodeSchedule<W>)> {
+        self.schedule.iter()
+    }
+}
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Runs the critical path method over the graph where every node
+    /// is a task that must wait for its parents to finish. The duration
+    /// of each task is computed by `duration`.
+    pub fn critical_path<W, F>(&self, mut duration: F) -> CriticalPath<W>
+    where
+        W: Copy + PartialOrd + Add<Output = W> + Sub<Output = W> + Default,
+        F: FnMut(Node<&Data>) -> W,
+    {
+        let durations = self
+            .topological_sort
+            .iter()
+            .map(|node_id| (node_id, duration(self.node_unchecked(node_id))))
+            .collect::<HashMap<_, _>>();
+
+        // Forward pass, sources first
+        let mut earliest_start: HashMap<&NodeId, W> = HashMap::new();
+        let mut critical_parent: HashMap<&NodeId, &NodeId> = HashMap::new();
+        let mut project_duration = W::default();
+        let mut last: Option<&NodeId> = None;
+        for node_id in self.topological_sort.iter().rev() {
+            let start = *earliest_start.entry(node_id).or_default();
+            let finish = start + durations[node_id];
+            if last.is_none() || finish > project_duration {
+                project_duration = finish;
+                last = Some(node_id);
+            }
+            for child in self.children(node_id).expect("Node must exist") {
+                let replace = match earliest_start.get(child) {
+                    Some(current) => finish > *current,
+                    None => true,
+                };
+                if replace {
+                    earliest_start.insert(child, finish);
+                    critical_parent.insert(child, node_id);
+                }
+            }
+        }
+
+        // Backward pass, sinks first
+        let mut latest_finish: HashMap<&NodeId, W> = HashMap::new();
+        for node_id in self.topological_sort.iter() {
+            let finish = self
+                .children(node_id)
+                .expect("Node must exist")
+                .map(|child| latest_finish[child] - durations[child])
+                .fold(
+                    project_duration,
+                    |min, start| if start < min { start } else { min },
+                );
+            latest_finish.insert(node_id, finish);
+        }
+
+        let schedule = self
+            .topological_sort
+            .iter()
+            .map(|node_id| {
+                let duration = durations[node_id];
+                let schedule = NodeSchedule {
+                    earliest_start: earliest_start[node_id],
+                    latest_start: latest_finish[node_id] - duration,
+                    duration,
+                };
+                (node_id.clone(), schedule)
+            })
+            .collect();
+
+        let mut path = Vec::new();
+        let mut current = last;
+        while let Some(node_id) = current {
+            path.push(node_id.clone());
+            current = critical_parent.get(node_id).copied();
+        }
+        path.reverse();
+
+        CriticalPath {
+            path,
+            duration: project_duration,
+            schedule,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_critical_path() {
+        let mut graph = DirectedGraph::<u32>::new();
+        let _ = graph.add_node("fetch", 2);
+        let _ = graph.add_node("clean", 3);
+        let _ = graph.add_node("lint", 1);
+        let _ = graph.add_node("model", 4);
+        let _ = graph.add_node("report", 1);
+        let _ = graph.add_edge("fetch", "clean");
+        let _ = graph.add_edge("fetch", "lint");
+        let _ = graph.add_edge("clean", "model");
+        let _ = graph.add_edge("lint", "report");
+        let _ = graph.add_edge("model", "report");
+
+        let graph = DirectedAcyclicGraph::build(graph).unwrap();
+        let critical = graph.critical_path(|node| **node.data());
+
+        assert_eq!(critical.path(), ["fetch", "clean", "model", "report"]);
+        assert_eq!(*critical.duration(), 10);
+
+        let lint = critical.schedule("lint").unwrap();
+        assert_eq!(lint.earliest_start(), 2);
+        assert_eq!(lint.latest_start(), 8);
+        assert_eq!(lint.slack(), 6);
+
+        for node_id in critical.path() {
+            assert_eq!(critical.schedule(node_id).unwrap().slack(), 0);
+        }
+
+        let report = critical.schedule("report").unwrap();
+        assert_eq!(report.earliest_finish(), 10);
+        assert_eq!(report.latest_finish(), 10);
+    }
+
+    #[test]
+    fn test_critical_path_disconnected() {
+        let mut graph = DirectedGraph::<f64>::new();
+        let _ = graph.add_node("a", 1.5);
+        let _ = graph.add_node("b", 4.0);
+
+        let graph = DirectedAcyclicGraph::build(graph).unwrap();
+        let critical = graph.critical_path(|node| **node.data());
+
+        assert_eq!(critical.path(), ["b"]);
+        assert_eq!(*critical.duration(), 4.0);
+        assert_eq!(critical.schedule("a").unwrap().slack(), 2.5);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// Number of children of every node, which drops to zero once all of
+/// them have been placed in an earlier generation
+fn remaining_children<Data, EdgeData>(dg: &DirectedGraph<Data, EdgeData>) -> Vec<usize> {
+    dg.children.iter().map(Vec::len).collect()
+}
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Splits the nodes into generations that follow the same order as
+    /// `topological_sort`. The first generation holds the nodes without
+    /// children and every other node belongs to the generation right
+    /// after the latest of its children, so the nodes of a generation
+    /// only depend on earlier generations and can run concurrently.
+    /// Nodes within a generation are sorted by id.
+    pub fn topological_generations(&self) -> Vec<Vec<NodeId>> {
+        let dg = &self.dg;
+        let mut remaining = remaining_children(dg);
+        let mut current = (0..dg.n_nodes() as u32)
+            .filter(|&node| remaining[node as usize] == 0)
+            .collect::<Vec<_>>();
+        let mut generations = Vec::new();
+
+        while !current.is_empty() {
+            let mut next = Vec::new();
+            for &node in &current {
+                for &parent in dg.parents_at(node) {
+                    remaining[parent as usize] -= 1;
+                    if remaining[parent as usize] == 0 {
+                        next.push(parent);
+                    }
+                }
+            }
+            let mut generation = current
+                .iter()
+                .map(|&node| dg.id_at(node).clone())
+                .collect::<Vec<_>>();
+            generation.sort_unstable();
+            generations.push(generation);
+            current = next;
+        }
+
+        generations
+    }
+
+    /// Generation of `node` in [`DirectedAcyclicGraph::topological_generations`],
+    /// the number of edges in the longest path from `node` to a node
+    /// without children
+    pub fn node_level(&self, node: impl AsRef<str>) -> GraphInteractionResult<usize> {
+        let mut descendants = self
+            .descendants(node)?
+            .include_start(true)
+            .collect::<Vec<_>>();
+        // Children come before their parents in the topological sort
+        descendants.sort_unstable_by_key(|node_id| self.topological_index(node_id));
+
+        let mut levels = HashMap::with_capacity(descendants.len());
+        let mut level = 0;
+        for node_id in descendants {
+            level = self
+                .children(&node_id)
+                .expect("Node must exist")
+                .map(|child| levels[child] + 1)
+                .max()
+                .unwrap_or(0);
+            levels.insert(node_id, level);
+        }
+        Ok(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline() -> DirectedAcyclicGraph<()> {
+        let mut graph = DirectedGraph::new();
+        for node in ["deploy", "test", "lint", "build", "fetch", "docs"] {
+            let _ = graph.add_node(node, ());
+        }
+        let _ = graph.add_path(&["deploy", "test", "build", "fetch"]);
+        let _ = graph.add_edge("deploy", "lint");
+        let _ = graph.add_edge("lint", "fetch");
+        let _ = graph.add_edge("docs", "fetch");
+        DirectedAcyclicGraph::build(graph).unwrap()
+    }
+
+    #[test]
+    fn test_topological_generations() {
+        let graph = pipeline();
+
+        assert_eq!(
+            graph.topological_generations(),
+            vec![
+                vec!["fetch"],
+                vec!["build", "docs", "lint"],
+                vec!["test"],
+                vec!["deploy"]
+            ]
+        );
+        assert!(DirectedAcyclicGraph::build(DirectedGraph::<()>::new())
+            .unwrap()
+            .topological_generations()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_node_level() {
+        let graph = pipeline();
+
+        for (level, generation) in graph.topological_generations().iter().enumerate() {
+            for node_id in generation {
+                assert_eq!(graph.node_level(node_id).unwrap(), level);
+            }
+        }
+        assert!(graph.node_level("missing").is_err());
+    }
+}
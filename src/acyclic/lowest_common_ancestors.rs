@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    fn inclusive_ancestors(&self, node_id: &NodeId) -> GraphInteractionResult<HashSet<NodeId>> {
+        Ok(self.ancestors(node_id)?.include_start(true).collect())
+    }
+
+    /// Keeps the common ancestors that have no child which is also a
+    /// common ancestor
+    fn deepest(&self, common: &HashSet<NodeId>) -> Vec<NodeId> {
+        let mut lowest = common
+            .iter()
+            .filter(|node_id| {
+                !self
+                    .children(node_id)
+                    .expect("Node must exist")
+                    .any(|child| common.contains(child))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        lowest.sort_unstable();
+        lowest
+    }
+
+    /// Finds the deepest nodes that are ancestors of every node in
+    /// `nodes`. A node is considered an ancestor of itself. In a DAG
+    /// there may be more than one lowest common ancestor, or none.
+    pub fn lowest_common_ancestors(
+        &self,
+        nodes: &[impl AsRef<str>],
+    ) -> GraphInteractionResult<Vec<NodeId>> {
+        let node_ids = nodes
+            .iter()
+            .map(|node| self.get_node_id(node))
+            .collect::<GraphInteractionResult<Vec<_>>>()?;
+
+        let Some((first, rest)) = node_ids.split_first() else {
+            return Ok(Vec::new());
+        };
+
+        let mut common = self.inclusive_ancestors(first)?;
+        for node_id in rest {
+            let ancestors = self.inclusive_ancestors(node_id)?;
+            common.retain(|ancestor| ancestors.contains(ancestor));
+        }
+
+        Ok(self.deepest(&common))
+    }
+
+    /// Finds the lowest common ancestors of many pairs of nodes. The
+    /// ancestors of every node are only computed once.
+    pub fn lowest_common_ancestors_pairs(
+        &self,
+        pairs: &[(impl AsRef<str>, impl AsRef<str>)],
+    ) -> GraphInteractionResult<Vec<Vec<NodeId>>> {
+        let mut cache: HashMap<NodeId, HashSet<NodeId>> = HashMap::new();
+        let mut result = Vec::with_capacity(pairs.len());
+
+        for (a, b) in pairs {
+            let a = self.get_node_id(a)?;
+            let b = self.get_node_id(b)?;
+            for node_id in [&a, &b] {
+                if !cache.contains_key(node_id) {
+                    let ancestors = self.inclusive_ancestors(node_id)?;
+                    cache.insert(node_id.clone(), ancestors);
+                }
+            }
+            let common = cache[&a]
+                .intersection(&cache[&b])
+                .cloned()
+                .collect::<HashSet<_>>();
+            result.push(self.deepest(&common));
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hierarchy() -> DirectedAcyclicGraph<()> {
+        let mut graph = DirectedGraph::new();
+        for node in ["root", "a", "b", "c", "d", "e", "x", "y"] {
+            let _ = graph.add_node(node, ());
+        }
+        let _ = graph.add_path(&["root", "a", "c"]);
+        let _ = graph.add_path(&["root", "b", "d"]);
+        let _ = graph.add_edge("a", "d");
+        let _ = graph.add_edge("b", "c");
+        let _ = graph.add_edge("c", "e");
+        let _ = graph.add_edge("x", "y");
+        DirectedAcyclicGraph::build(graph).unwrap()
+    }
+
+    #[test]
+    fn test_lowest_common_ancestors() {
+        let graph = hierarchy();
+
+        assert_eq!(
+            graph.lowest_common_ancestors(&["c", "d"]).unwrap(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            graph.lowest_common_ancestors(&["e", "c"]).unwrap(),
+            vec!["c"]
+        );
+        assert_eq!(
+            graph.lowest_common_ancestors(&["a", "b"]).unwrap(),
+            vec!["root"]
+        );
+        assert_eq!(
+            graph.lowest_common_ancestors(&["e", "d", "a"]).unwrap(),
+            vec!["a"]
+        );
+        assert_eq!(
+            graph.lowest_common_ancestors(&["e", "y"]).unwrap(),
+            Vec::<NodeId>::new()
+        );
+        assert!(graph.lowest_common_ancestors(&["e", "z"]).is_err());
+    }
+
+    #[test]
+    fn test_lowest_common_ancestors_pairs() {
+        let graph = hierarchy();
+
+        let result = graph
+            .lowest_common_ancestors_pairs(&[("c", "d"), ("e", "c"), ("x", "y")])
+            .unwrap();
+
+        assert_eq!(result, vec![vec!["a", "b"], vec!["c"], vec!["x"]]);
+    }
+}
@@ -1,39 +1,75 @@
+use crate::algo::{all_paths_dfs, topological_sort};
 use crate::prelude::*;
 use std::ops::Deref;
-mod topological_sort;
+mod critical_path;
+mod generations;
+mod lowest_common_ancestors;
+mod mutation;
+mod order;
+mod paths;
+mod reachability;
+mod subgraph;
+mod transitive;
+mod validate;
+pub use critical_path::{CriticalPath, NodeSchedule};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use topological_sort::topological_sort;
+use std::collections::HashSet;
 
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct DirectedAcyclicGraph<Data> {
-    pub(crate) dg: Box<DirectedGraph<Data>>,
-    pub(crate) topological_sort: Vec<NodeId>,
+pub struct DirectedAcyclicGraph<Data, EdgeData = ()> {
+    pub(crate) dg: Box<DirectedGraph<Data, EdgeData>>,
+    pub(crate) topological_sort: order::TopologicalOrder,
+    /// Opt-in index built by `build_reachability_index`, dropped on
+    /// every mutation of the graph
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) reachability: Option<reachability::ReachabilityIndex>,
 }
 
-impl<Data> Clone for DirectedAcyclicGraph<Data>
+impl<Data, EdgeData> Clone for DirectedAcyclicGraph<Data, EdgeData>
 where
     Data: Clone,
+    EdgeData: Clone,
 {
     fn clone(&self) -> Self {
         DirectedAcyclicGraph {
             dg: self.dg.clone(),
             topological_sort: self.topological_sort.clone(),
+            reachability: self.reachability.clone(),
         }
     }
 }
 
-impl<Data> DirectedAcyclicGraph<Data> {
-    pub fn build(dg: DirectedGraph<Data>) -> Result<DirectedAcyclicGraph<Data>, GraphHasCycle> {
-        let topological_sort = topological_sort::<Data>(&dg)?;
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    pub fn build(
+        dg: DirectedGraph<Data, EdgeData>,
+    ) -> GraphInteractionResult<DirectedAcyclicGraph<Data, EdgeData>> {
+        let topological_sort = topological_sort(&dg)?.into();
         Ok(DirectedAcyclicGraph {
             dg: Box::new(dg),
             topological_sort,
+            reachability: None,
         })
     }
-    pub fn into_inner(self) -> DirectedGraph<Data> {
+    pub fn into_inner(self) -> DirectedGraph<Data, EdgeData> {
         *self.dg
     }
+    /// Edge data can be modified freely since it does not
+    /// affect the topology of the graph
+    pub fn edge_data_mut(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<&mut EdgeData> {
+        self.dg.edge_data_mut(from, to)
+    }
+    /// Position of the node in the cached topological sort
+    pub(crate) fn topological_index(&self, id: &NodeId) -> usize {
+        self.topological_sort
+            .position(id)
+            .expect("Node must be included in topo_order")
+    }
     /// Finds path using topological sort
     pub fn find_path(
         &self,
@@ -46,74 +82,60 @@ impl<Data> DirectedAcyclicGraph<Data> {
             return Ok(Some(vec![start_id]));
         }
 
-        let topo_order = self.topological_sort.as_slice();
-        let start_index = topo_order
-            .iter()
-            .position(|id| id == &start_id)
-            .expect("Node must be included in topo_order");
-        let goal_index = topo_order
-            .iter()
-            .position(|id| id == &goal_id)
-            .expect("Node must be included in topo_order");
+        let start_index = self.topological_index(&start_id);
+        let goal_index = self.topological_index(&goal_id);
 
         if goal_index > start_index {
             return Ok(None); // No path from start to goal in a DAG if start comes after goal in topo order
         }
 
-        let mut path = Vec::new();
-        let mut current = goal_id.clone();
-        path.push(current.clone());
-
-        // Explore the path using the topological order
-        for node_id in &topo_order[goal_index..=start_index] {
-            if self.edge_exists(node_id, &current) {
-                path.push(node_id.clone());
-                current = node_id.clone();
-                if current == start_id {
-                    path.reverse();
-                    return Ok(Some(path));
-                }
+        // Walks parents backwards from the goal, trying the ones closest
+        // in the topological sort first. Parents placed after the start
+        // cannot be reached from it and are skipped, and nodes that did
+        // not lead to the start are never explored twice.
+        let mut stack = vec![(
+            goal_id.clone(),
+            self.parents_before(&goal_id, start_index),
+            0,
+        )];
+        let mut dead_ends = HashSet::new();
+        while let Some((_, parents, cursor)) = stack.last_mut() {
+            let Some(parent) = parents.get(*cursor).cloned() else {
+                let (node_id, _, _) = stack.pop().expect("Stack is not empty");
+                dead_ends.insert(node_id);
+                continue;
+            };
+            *cursor += 1;
+            if parent == start_id {
+                let mut path = vec![start_id];
+                path.extend(stack.into_iter().rev().map(|(node_id, _, _)| node_id));
+                return Ok(Some(path));
+            }
+            if !dead_ends.contains(&parent) {
+                let parents = self.parents_before(&parent, start_index);
+                stack.push((parent, parents, 0));
             }
         }
 
         Ok(None)
     }
+    /// Parents of `node_id` placed at or before `max_index` in the
+    /// topological sort, closest first
+    fn parents_before(&self, node_id: &NodeId, max_index: usize) -> Vec<NodeId> {
+        let mut parents = self
+            .dg
+            .neighbors_of(node_id, Direction::Incoming)
+            .filter(|parent| self.topological_index(parent) <= max_index)
+            .cloned()
+            .collect::<Vec<_>>();
+        parents.sort_unstable_by_key(|parent| self.topological_index(parent));
+        parents
+    }
     pub fn find_all_paths(
         &self,
         from: impl AsRef<str>,
         to: impl AsRef<str>,
     ) -> GraphInteractionResult<Vec<Vec<NodeId>>> {
-        // Helper function to perform DFS
-        fn dfs<Data>(
-            graph: &DirectedAcyclicGraph<Data>,
-            current: NodeId,
-            goal_id: NodeId,
-            current_path: &mut Vec<NodeId>,
-            all_paths: &mut Vec<Vec<NodeId>>,
-        ) {
-            // Add current node to path
-            current_path.push(current.clone());
-
-            // Check if the current node is the goal
-            if current == goal_id {
-                all_paths.push(current_path.clone());
-            } else {
-                // Continue to next nodes that can be visited from the current node
-                for child in graph.children(&current).expect("Node must exist") {
-                    dfs(
-                        graph,
-                        child.clone(),
-                        goal_id.clone(),
-                        current_path,
-                        all_paths,
-                    );
-                }
-            }
-
-            // Backtrack to explore another path
-            current_path.pop();
-        }
-
         let start_id = self.get_node_id(&from)?;
         let goal_id = self.get_node_id(&to)?;
 
@@ -121,14 +143,14 @@ impl<Data> DirectedAcyclicGraph<Data> {
         let mut current_path = Vec::new();
 
         // Start DFS from the start node
-        dfs(self, start_id, goal_id, &mut current_path, &mut all_paths);
+        all_paths_dfs(self, start_id, &goal_id, &mut current_path, &mut all_paths);
 
         Ok(all_paths)
     }
 }
 
-impl<Data> Deref for DirectedAcyclicGraph<Data> {
-    type Target = DirectedGraph<Data>;
+impl<Data, EdgeData> Deref for DirectedAcyclicGraph<Data, EdgeData> {
+    type Target = DirectedGraph<Data, EdgeData>;
     fn deref(&self) -> &Self::Target {
         &self.dg
     }
@@ -151,7 +173,7 @@ mod tests {
         let _ = graph.add_edge("3", "4");
         let _ = graph.add_edge("4", "5");
 
-        assert!(topological_sort::<()>(&graph).is_ok());
+        assert!(topological_sort(&graph).is_ok());
     }
 
     #[test]
@@ -168,7 +190,44 @@ mod tests {
         let _ = graph.add_edge("4", "5");
         let _ = graph.add_edge("5", "1");
 
-        assert!(topological_sort::<()>(&graph).is_err());
+        assert!(topological_sort(&graph).is_err());
+    }
+
+    #[test]
+    fn test_topologically_sort_cycle_witness() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("a", ());
+        let _ = graph.add_node("b", ());
+        let _ = graph.add_node("c", ());
+        let _ = graph.add_node("d", ());
+        let _ = graph.add_node("e", ());
+        let _ = graph.add_path(&["a", "b", "c", "a"]);
+        let _ = graph.add_edge("d", "a");
+        let _ = graph.add_edge("c", "e");
+
+        let Err(OrbweaverError::Cycle(err)) = DirectedAcyclicGraph::build(graph) else {
+            panic!("Expected a cycle");
+        };
+
+        assert_eq!(err.cycle, vec!["a", "b", "c"]);
+        assert_eq!(err.unsorted, vec!["a", "b", "c", "d"]);
+        assert_eq!(
+            err.to_string(),
+            "Unable to topologically sort, graph has at least one cycle: a -> b -> c -> a"
+        );
+    }
+
+    #[test]
+    fn test_topologically_sort_self_loop() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("a", ());
+        let _ = graph.add_edge("a", "a");
+
+        let Err(OrbweaverError::Cycle(err)) = topological_sort(&graph) else {
+            panic!("Expected a cycle");
+        };
+
+        assert_eq!(err.cycle, vec!["a"]);
     }
 
     #[test]
@@ -212,6 +271,43 @@ mod tests {
         assert_eq!(path.unwrap().len(), 5);
     }
 
+    #[test]
+    fn test_find_path_skips_parents_not_reachable_from_start() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("goal", ());
+        let _ = graph.add_node("start", ());
+        let _ = graph.add_node("x", ());
+        let _ = graph.add_edge("start", "goal");
+        let _ = graph.add_edge("x", "goal");
+
+        let graph = DirectedAcyclicGraph::build(graph).unwrap();
+
+        assert_eq!(
+            graph.find_path("start", "goal").unwrap(),
+            Some(vec!["start".into(), "goal".into()])
+        );
+        assert_eq!(graph.find_path("x", "start").unwrap(), None);
+    }
+
+    #[test]
+    fn test_find_path_deep_chain() {
+        let mut graph = DirectedGraph::<()>::new();
+        let n = 200_000;
+        for i in 0..n {
+            let _ = graph.add_node(i.to_string(), ());
+        }
+        for i in 1..n {
+            let _ = graph.add_edge((i - 1).to_string(), i.to_string());
+        }
+
+        let graph = DirectedAcyclicGraph::build(graph).unwrap();
+
+        let path = graph.find_path("0", (n - 1).to_string()).unwrap().unwrap();
+        assert_eq!(path.len(), n);
+        assert_eq!(path[0].as_ref(), "0");
+        assert_eq!(graph.find_path((n - 1).to_string(), "0").unwrap(), None);
+    }
+
     #[test]
     fn test_find_all_paths_many_paths() {
         let mut graph = DirectedGraph::<()>::new();
@@ -237,4 +333,18 @@ mod tests {
         assert_eq!(paths[0].len(), 5);
         assert_eq!(paths[1].len(), 2);
     }
+
+    #[test]
+    fn test_edge_data_is_kept_in_dag() {
+        let mut graph = DirectedGraph::<(), u32>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_edge_with("0", "1", 7);
+
+        let mut graph = DirectedAcyclicGraph::build(graph).unwrap();
+        *graph.edge_data_mut("0", "1").unwrap() += 1;
+
+        assert_eq!(**graph.get_edge("0", "1").unwrap().data(), 8);
+        assert_eq!(**graph.into_inner().get_edge("0", "1").unwrap().data(), 8);
+    }
 }
@@ -0,0 +1,326 @@
+use crate::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+impl<Data, EdgeData: Default> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Adds an edge with the default edge data. See
+    /// [`DirectedAcyclicGraph::add_edge_with`].
+    pub fn add_edge(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<&mut Self> {
+        self.add_edge_with(from, to, EdgeData::default())
+    }
+}
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Adds a node without any edges. New nodes are placed at the end
+    /// of the topological sort.
+    pub fn add_node(
+        &mut self,
+        id: impl AsRef<str>,
+        data: Data,
+    ) -> GraphInteractionResult<&mut Self> {
+        self.dg.add_node(&id, data)?;
+        self.reachability = None;
+        let node_id = self.dg.get_node_id(&id).expect("Node was just added");
+        self.topological_sort.push(node_id.clone());
+        debug_assert!(self.validate_order_of(&node_id).is_ok());
+        Ok(self)
+    }
+
+    /// Adds an edge carrying `data` if it does not create a cycle.
+    ///
+    /// The topological sort is kept up to date incrementally using the
+    /// Pearce-Kelly algorithm, so only the nodes between `from` and `to`
+    /// in the current order are visited.
+    pub fn add_edge_with(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        data: EdgeData,
+    ) -> GraphInteractionResult<&mut Self> {
+        let from_id = self.get_node_id(&from)?;
+        let to_id = self.get_node_id(&to)?;
+
+        if from_id == to_id {
+            return Err(OrbweaverError::EdgeCreatesCycle(vec![from_id]));
+        }
+
+        // Parents are always placed after their children in the order
+        let lower_bound = self.topological_index(&from_id);
+        let upper_bound = self.topological_index(&to_id);
+        if lower_bound < upper_bound {
+            let descendants = self.descendants_in_range(&to_id, &from_id, lower_bound)?;
+            let ancestors = self.ancestors_in_range(&from_id, upper_bound)?;
+            self.reorder(descendants, ancestors);
+        }
+
+        self.dg.add_edge_with(&from_id, &to_id, data)?;
+        self.reachability = None;
+        debug_assert!(self.validate_order_of(&from_id).is_ok());
+        Ok(self)
+    }
+
+    /// See [`DirectedGraph::add_edge_with_policy`]. Existing edges are
+    /// handled by the inner graph since the order does not change.
+    pub fn add_edge_with_policy(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        data: EdgeData,
+        policy: OnDuplicateEdge,
+    ) -> Result<&mut Self, OrbweaverError> {
+        if self.edge_exists(&from, &to) {
+            self.dg.add_edge_with_policy(from, to, data, policy)?;
+            return Ok(self);
+        }
+        self.add_edge_with(from, to, data)
+    }
+
+    /// Removing an edge never invalidates the topological sort
+    pub fn remove_edge(&mut self, from: impl AsRef<str>, to: impl AsRef<str>) -> &mut Self {
+        self.dg.remove_edge(from, to);
+        self.reachability = None;
+        self
+    }
+
+    pub fn remove_node(&mut self, node_id: impl AsRef<str>) -> &mut Self {
+        self.topological_sort.remove(node_id.as_ref());
+        self.dg.remove_node(node_id);
+        self.reachability = None;
+        debug_assert_eq!(self.topological_sort.len(), self.dg.n_nodes());
+        self
+    }
+
+    /// See [`DirectedGraph::try_remove_edge`]
+    pub fn try_remove_edge(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> Result<EdgeData, OrbweaverError> {
+        let data = self.dg.try_remove_edge(from, to)?;
+        self.reachability = None;
+        Ok(data)
+    }
+
+    /// See [`DirectedGraph::try_remove_node`]
+    pub fn try_remove_node(&mut self, node_id: impl AsRef<str>) -> Result<Data, OrbweaverError> {
+        let data = self.dg.try_remove_node(&node_id)?;
+        self.topological_sort.remove(node_id.as_ref());
+        self.reachability = None;
+        debug_assert_eq!(self.topological_sort.len(), self.dg.n_nodes());
+        Ok(data)
+    }
+
+    /// Nodes reachable from `start` that are placed at or after
+    /// `lower_bound`. Fails if `target` is reachable since adding the
+    /// edge `target -> start` would then close a cycle.
+    fn descendants_in_range(
+        &self,
+        start: &NodeId,
+        target: &NodeId,
+        lower_bound: usize,
+    ) -> GraphInteractionResult<Vec<NodeId>> {
+        let mut visited = HashSet::new();
+        let mut reached_from: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut to_visit = vec![start.clone()];
+        visited.insert(start.clone());
+
+        while let Some(node_id) = to_visit.pop() {
+            for child in self.children(&node_id)? {
+                if child == target {
+                    let mut cycle = vec![node_id.clone()];
+                    let mut current = &node_id;
+                    while let Some(parent) = reached_from.get(current) {
+                        cycle.push(parent.clone());
+                        current = parent;
+                    }
+                    cycle.push(target.clone());
+                    cycle.reverse();
+                    return Err(OrbweaverError::EdgeCreatesCycle(cycle));
+                }
+                if self.topological_index(child) >= lower_bound && visited.insert(child.clone()) {
+                    reached_from.insert(child.clone(), node_id.clone());
+                    to_visit.push(child.clone());
+                }
+            }
+        }
+
+        Ok(visited.into_iter().collect())
+    }
+
+    /// Nodes that can reach `start` that are placed at or before
+    /// `upper_bound`.
+    fn ancestors_in_range(
+        &self,
+        start: &NodeId,
+        upper_bound: usize,
+    ) -> GraphInteractionResult<Vec<NodeId>> {
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![start.clone()];
+        visited.insert(start.clone());
+
+        while let Some(node_id) = to_visit.pop() {
+            for parent in self.parents(&node_id)? {
+                if self.topological_index(parent) <= upper_bound && visited.insert(parent.clone()) {
+                    to_visit.push(parent.clone());
+                }
+            }
+        }
+
+        Ok(visited.into_iter().collect())
+    }
+
+    /// Reuses the positions taken by both sets, placing every descendant
+    /// before every ancestor while keeping their relative order.
+    fn reorder(&mut self, mut descendants: Vec<NodeId>, mut ancestors: Vec<NodeId>) {
+        descendants.sort_unstable_by_key(|node_id| self.topological_index(node_id));
+        ancestors.sort_unstable_by_key(|node_id| self.topological_index(node_id));
+
+        let mut positions = descendants
+            .iter()
+            .chain(ancestors.iter())
+            .map(|node_id| self.topological_index(node_id))
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+
+        for (node_id, &position) in descendants.iter().chain(&ancestors).zip(&positions) {
+            self.topological_sort.place(node_id.clone(), position);
+        }
+        debug_assert!(descendants
+            .iter()
+            .chain(&ancestors)
+            .all(|node_id| self.validate_order_of(node_id).is_ok()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_valid_topological_sort<Data, EdgeData>(graph: &DirectedAcyclicGraph<Data, EdgeData>) {
+        assert_eq!(graph.topological_sort.len(), graph.n_nodes());
+        for (position, node_id) in graph.topological_sort.slots().iter().enumerate() {
+            let Some(node_id) = node_id else {
+                continue;
+            };
+            assert_eq!(graph.topological_index(node_id), position);
+            for child in graph.children(node_id).unwrap() {
+                assert!(graph.topological_index(child) < position);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_nodes_and_edges_to_dag() {
+        let mut graph = DirectedAcyclicGraph::build(DirectedGraph::<()>::new()).unwrap();
+        graph
+            .add_node("0", ())
+            .unwrap()
+            .add_node("1", ())
+            .unwrap()
+            .add_node("2", ())
+            .unwrap()
+            .add_node("3", ())
+            .unwrap();
+
+        graph.add_edge("0", "1").unwrap();
+        assert_valid_topological_sort(&graph);
+        graph.add_edge("1", "2").unwrap();
+        assert_valid_topological_sort(&graph);
+        graph.add_edge("3", "0").unwrap();
+        assert_valid_topological_sort(&graph);
+        graph.add_edge("3", "2").unwrap();
+        assert_valid_topological_sort(&graph);
+
+        assert_eq!(
+            graph.find_path("3", "2").unwrap().unwrap().first().unwrap(),
+            "3"
+        );
+        assert!(graph.add_node("3", ()).is_err());
+    }
+
+    #[test]
+    fn test_add_edge_rejects_cycles() {
+        let mut graph = DirectedAcyclicGraph::build(DirectedGraph::<()>::new()).unwrap();
+        for node in ["a", "b", "c", "d"] {
+            graph.add_node(node, ()).unwrap();
+        }
+        graph.add_edge("a", "b").unwrap();
+        graph.add_edge("b", "c").unwrap();
+        graph.add_edge("c", "d").unwrap();
+
+        match graph.add_edge("d", "a") {
+            Err(OrbweaverError::EdgeCreatesCycle(cycle)) => {
+                assert_eq!(cycle, vec!["d", "a", "b", "c"]);
+            }
+            _ => panic!("Expected the edge to be rejected"),
+        }
+        assert!(matches!(
+            graph.add_edge("b", "b"),
+            Err(OrbweaverError::EdgeCreatesCycle(_))
+        ));
+        assert!(!graph.edge_exists("d", "a"));
+        assert_valid_topological_sort(&graph);
+    }
+
+    #[test]
+    fn test_add_edge_reorders_many_nodes() {
+        let mut graph = DirectedAcyclicGraph::build(DirectedGraph::<()>::new()).unwrap();
+        for i in 0..20 {
+            graph.add_node(i.to_string(), ()).unwrap();
+        }
+        // Adding edges against the insertion order forces a reorder each time
+        for i in 0..19 {
+            graph.add_edge((i + 1).to_string(), i.to_string()).unwrap();
+            assert_valid_topological_sort(&graph);
+        }
+        for i in (0..18).step_by(3) {
+            graph.add_edge((i + 2).to_string(), i.to_string()).unwrap();
+            assert_valid_topological_sort(&graph);
+        }
+        assert!(graph.add_edge("0", "19").is_err());
+    }
+
+    #[test]
+    fn test_remove_node_and_edge_from_dag() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_path(&["0", "1", "2"]);
+        let mut graph = DirectedAcyclicGraph::build(graph).unwrap();
+
+        graph.remove_edge("1", "2");
+        assert!(!graph.edge_exists("1", "2"));
+        graph.add_edge("2", "1").unwrap();
+        assert_valid_topological_sort(&graph);
+
+        graph.remove_node("1");
+        assert_valid_topological_sort(&graph);
+        assert!(graph.get_node("1").is_err());
+        graph.add_edge("2", "0").unwrap();
+        assert_valid_topological_sort(&graph);
+    }
+
+    #[test]
+    fn test_try_remove_from_dag() {
+        let mut graph = DirectedGraph::<u32, &str>::new();
+        let _ = graph.add_node("0", 0);
+        let _ = graph.add_node("1", 1);
+        let _ = graph.add_node("2", 2);
+        let _ = graph.add_edge_with("0", "1", "01");
+        let _ = graph.add_edge_with("1", "2", "12");
+        let mut graph = DirectedAcyclicGraph::build(graph).unwrap();
+
+        assert_eq!(graph.try_remove_edge("0", "1").unwrap(), "01");
+        assert!(graph.try_remove_edge("0", "1").is_err());
+        assert_eq!(graph.try_remove_node("1").unwrap(), 1);
+        assert!(graph.try_remove_node("1").is_err());
+        assert_eq!(graph.topological_sort.len(), 2);
+        assert_valid_topological_sort(&graph);
+        graph.add_edge("2", "0").unwrap();
+        assert_valid_topological_sort(&graph);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::NodeId;
+use std::collections::HashMap;
+
+/// Cached topological sort of a DAG, children first.
+///
+/// Removing a node leaves a tombstone in its slot, so the positions of
+/// the other nodes stay valid since only their relative order matters.
+/// Tombstones are dropped in a single pass once they outnumber the
+/// nodes, which keeps removals amortised constant time.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TopologicalOrder {
+    slots: Vec<Option<NodeId>>,
+    positions: HashMap<NodeId, usize>,
+}
+
+impl From<Vec<NodeId>> for TopologicalOrder {
+    fn from(value: Vec<NodeId>) -> Self {
+        let positions = value
+            .iter()
+            .enumerate()
+            .map(|(position, node_id)| (node_id.clone(), position))
+            .collect();
+        TopologicalOrder {
+            slots: value.into_iter().map(Some).collect(),
+            positions,
+        }
+    }
+}
+
+impl PartialEq for TopologicalOrder {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl TopologicalOrder {
+    pub(crate) fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Position of the node, every child is placed before its parents.
+    /// Positions are not contiguous once nodes have been removed.
+    pub(crate) fn position(&self, node_id: &str) -> Option<usize> {
+        self.positions.get(node_id).copied()
+    }
+
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = &NodeId> {
+        self.slots.iter().flatten()
+    }
+
+    /// Nodes placed at or before `position`
+    pub(crate) fn iter_through(&self, position: usize) -> impl DoubleEndedIterator<Item = &NodeId> {
+        self.slots[..=position].iter().flatten()
+    }
+
+    /// Every position, with `None` for the ones left by removed nodes
+    pub(crate) fn slots(&self) -> &[Option<NodeId>] {
+        &self.slots
+    }
+
+    /// Places the node after every other node
+    pub(crate) fn push(&mut self, node_id: NodeId) {
+        self.positions.insert(node_id.clone(), self.slots.len());
+        self.slots.push(Some(node_id));
+    }
+
+    /// Moves the node to `position`, which must be free or taken by a
+    /// node that is also being moved
+    pub(crate) fn place(&mut self, node_id: NodeId, position: usize) {
+        self.positions.insert(node_id.clone(), position);
+        self.slots[position] = Some(node_id);
+    }
+
+    pub(crate) fn remove(&mut self, node_id: &str) {
+        if let Some(position) = self.positions.remove(node_id) {
+            self.slots[position] = None;
+            if self.slots.len() > 2 * self.len() {
+                self.compact();
+            }
+        }
+    }
+
+    fn compact(&mut self) {
+        self.slots.retain(Option::is_some);
+        for (position, node_id) in self.slots.iter().flatten().enumerate() {
+            *self
+                .positions
+                .get_mut(node_id)
+                .expect("Every slot has a position") = position;
+        }
+    }
+}
+
+/// Stored as the plain list of nodes, so positions and tombstones are
+/// rebuilt on load
+#[cfg(feature = "serde")]
+impl serde::Serialize for TopologicalOrder {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TopologicalOrder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<NodeId>::deserialize(deserializer).map(TopologicalOrder::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removal_keeps_positions_until_compaction() {
+        let mut order = TopologicalOrder::from(
+            ["a", "b", "c", "d", "e"]
+                .into_iter()
+                .map(NodeId::from)
+                .collect::<Vec<_>>(),
+        );
+
+        order.remove("b");
+        order.remove("x");
+        assert_eq!(order.len(), 4);
+        assert_eq!(order.position("c"), Some(2));
+        assert_eq!(order.iter().collect::<Vec<_>>(), vec!["a", "c", "d", "e"]);
+        assert_eq!(order.iter_through(2).collect::<Vec<_>>(), vec!["a", "c"]);
+
+        order.remove("a");
+        assert_eq!(order.slots().len(), 5);
+        order.remove("e");
+        assert_eq!(order.slots(), &[Some("c".into()), Some("d".into())]);
+        assert_eq!(order.position("d"), Some(1));
+
+        order.push("f".into());
+        assert_eq!(order.position("f"), Some(2));
+    }
+}
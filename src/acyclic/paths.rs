@@ -0,0 +1,217 @@
+use crate::directed::construct_path;
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::ops::Add;
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Relaxes every edge reachable from `start_id` once, following the
+    /// topological order. `is_better` decides whether a new cost should
+    /// replace the current one.
+    fn relax_from<W, F>(
+        &self,
+        start_id: &NodeId,
+        mut weight: F,
+        is_better: impl Fn(&W, &W) -> bool,
+    ) -> (HashMap<NodeId, W>, HashMap<NodeId, NodeId>)
+    where
+        W: Copy + PartialOrd + Add<Output = W> + Default,
+        F: FnMut(Node<&Data>, Node<&Data>) -> W,
+    {
+        let start_index = self.topological_index(start_id);
+
+        let mut dist: HashMap<NodeId, W> = HashMap::new();
+        let mut prev: HashMap<NodeId, NodeId> = HashMap::new();
+        dist.insert(start_id.clone(), W::default());
+
+        // Nodes reachable from the start are always placed before it
+        for node_id in self.topological_sort.iter_through(start_index).rev() {
+            let Some(&cost) = dist.get(node_id) else {
+                continue;
+            };
+            for child in self.children(node_id).expect("Node must exist") {
+                let next_cost =
+                    cost + weight(self.node_unchecked(node_id), self.node_unchecked(child));
+                let replace = match dist.get(child) {
+                    Some(current) => is_better(&next_cost, current),
+                    None => true,
+                };
+                if replace {
+                    dist.insert(child.clone(), next_cost);
+                    prev.insert(child.clone(), node_id.clone());
+                }
+            }
+        }
+
+        (dist, prev)
+    }
+
+    fn weighted_path_from<W, F>(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        weight: F,
+        is_better: impl Fn(&W, &W) -> bool,
+    ) -> GraphInteractionResult<Option<WeightedPath<W>>>
+    where
+        W: Copy + PartialOrd + Add<Output = W> + Default,
+        F: FnMut(Node<&Data>, Node<&Data>) -> W,
+    {
+        let start_id = self.get_node_id(&from)?;
+        let goal_id = self.get_node_id(&to)?;
+        let (dist, prev) = self.relax_from(&start_id, weight, is_better);
+        Ok(dist
+            .get(&goal_id)
+            .map(|&cost| WeightedPath::new(construct_path(&prev, &start_id, &goal_id), cost)))
+    }
+
+    /// Finds the cheapest path in linear time using the topological
+    /// order. Unlike Dijkstra negative weights are allowed.
+    pub fn shortest_path_by<W, F>(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        weight: F,
+    ) -> GraphInteractionResult<Option<WeightedPath<W>>>
+    where
+        W: Copy + PartialOrd + Add<Output = W> + Default,
+        F: FnMut(Node<&Data>, Node<&Data>) -> W,
+    {
+        self.weighted_path_from(from, to, weight, |new, current| new < current)
+    }
+
+    /// Finds the most expensive path in linear time using the
+    /// topological order.
+    pub fn longest_path_by<W, F>(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        weight: F,
+    ) -> GraphInteractionResult<Option<WeightedPath<W>>>
+    where
+        W: Copy + PartialOrd + Add<Output = W> + Default,
+        F: FnMut(Node<&Data>, Node<&Data>) -> W,
+    {
+        self.weighted_path_from(from, to, weight, |new, current| new > current)
+    }
+
+    /// Finds the path with the most edges in the whole graph
+    pub fn longest_path(&self) -> Vec<NodeId> {
+        let mut length: HashMap<&NodeId, usize> = HashMap::new();
+        let mut prev: HashMap<&NodeId, &NodeId> = HashMap::new();
+        let mut end: Option<&NodeId> = None;
+
+        for node_id in self.topological_sort.iter().rev() {
+            let current = *length.entry(node_id).or_insert(0);
+            let is_longer = match end {
+                Some(end) => current > length[end],
+                None => true,
+            };
+            if is_longer {
+                end = Some(node_id);
+            }
+            for child in self.children(node_id).expect("Node must exist") {
+                let child_length = length.entry(child).or_insert(0);
+                if current + 1 > *child_length {
+                    *child_length = current + 1;
+                    prev.insert(child, node_id);
+                }
+            }
+        }
+
+        let mut path = Vec::new();
+        let mut current = end;
+        while let Some(node_id) = current {
+            path.push(node_id.clone());
+            current = prev.get(node_id).copied();
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted_dag() -> DirectedAcyclicGraph<i64> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("0", 0);
+        let _ = graph.add_node("1", 1);
+        let _ = graph.add_node("2", 5);
+        let _ = graph.add_node("3", 1);
+        let _ = graph.add_node("4", 1);
+        let _ = graph.add_node("5", 1);
+        let _ = graph.add_edge("0", "1");
+        let _ = graph.add_edge("1", "3");
+        let _ = graph.add_edge("0", "2");
+        let _ = graph.add_edge("2", "3");
+        let _ = graph.add_edge("3", "4");
+        let _ = graph.add_edge("4", "5");
+        let _ = graph.add_edge("3", "5");
+        DirectedAcyclicGraph::build(graph).unwrap()
+    }
+
+    #[test]
+    fn test_dag_shortest_path_by() {
+        let graph = weighted_dag();
+
+        let path = graph
+            .shortest_path_by("0", "5", |_, to| **to.data())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(path.path(), ["0", "1", "3", "5"]);
+        assert_eq!(*path.cost(), 3);
+    }
+
+    #[test]
+    fn test_dag_shortest_path_by_negative_weights() {
+        let graph = weighted_dag();
+
+        let path = graph
+            .shortest_path_by("0", "5", |_, to| -**to.data())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(path.path(), ["0", "2", "3", "4", "5"]);
+        assert_eq!(*path.cost(), -8);
+    }
+
+    #[test]
+    fn test_dag_longest_path_by() {
+        let graph = weighted_dag();
+
+        let path = graph
+            .longest_path_by("0", "5", |_, to| **to.data())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(path.path(), ["0", "2", "3", "4", "5"]);
+        assert_eq!(*path.cost(), 8);
+
+        assert_eq!(
+            graph
+                .longest_path_by("5", "0", |_, to| **to.data())
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dag_longest_path() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_node("3", ());
+        let _ = graph.add_node("4", ());
+        let _ = graph.add_edge("0", "4");
+        let _ = graph.add_edge("1", "2");
+        let _ = graph.add_edge("2", "3");
+        let _ = graph.add_edge("3", "4");
+
+        let graph = DirectedAcyclicGraph::build(graph).unwrap();
+
+        assert_eq!(graph.longest_path(), vec!["1", "2", "3", "4"]);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+
+/// Average number of intervals per node above which the reachability
+/// index is not built
+const MAX_INTERVALS_PER_NODE: usize = 64;
+
+/// Compressed transitive closure using interval labels. Nodes are
+/// numbered in post order over a spanning forest, so every subtree is a
+/// contiguous range of numbers, and each node keeps the merged ranges of
+/// the numbers it can reach. Trees need a single range per node and
+/// graphs close to a tree only a few. Indexed by the position of the
+/// nodes in the topological sort.
+#[derive(Debug, Clone)]
+pub(crate) struct ReachabilityIndex {
+    post_order: Vec<u32>,
+    /// Start and end in `intervals` of the ranges of every node
+    labels: Vec<(usize, usize)>,
+    intervals: Vec<(u32, u32)>,
+}
+
+impl ReachabilityIndex {
+    /// Gives up and returns `None` once the labels hold more than
+    /// `max_intervals` ranges
+    fn build<Data, EdgeData>(
+        graph: &DirectedAcyclicGraph<Data, EdgeData>,
+        max_intervals: usize,
+    ) -> Option<Self> {
+        let slots = graph.topological_sort.slots();
+        let n_slots = slots.len();
+        let children = slots
+            .iter()
+            .map(|slot| {
+                let mut children = slot
+                    .iter()
+                    .flat_map(|node_id| graph.children(node_id).expect("Node must exist"))
+                    .map(|child| graph.topological_index(child) as u32)
+                    .collect::<Vec<_>>();
+                children.sort_unstable();
+                children
+            })
+            .collect::<Vec<_>>();
+
+        let mut post_order = vec![0; n_slots];
+        let mut subtree_start = vec![0; n_slots];
+        let mut visited = vec![false; n_slots];
+        let mut next = 0;
+        for (root, slot) in slots.iter().enumerate() {
+            let Some(node_id) = slot else {
+                continue;
+            };
+            if !graph.parents(node_id).expect("Node must exist").is_empty() {
+                continue;
+            }
+            visited[root] = true;
+            subtree_start[root] = next;
+            let mut stack = vec![(root, 0)];
+            while let Some((node, cursor)) = stack.last_mut() {
+                let node = *node;
+                match children[node].get(*cursor) {
+                    Some(&child) => {
+                        *cursor += 1;
+                        let child = child as usize;
+                        if !visited[child] {
+                            visited[child] = true;
+                            subtree_start[child] = next;
+                            stack.push((child, 0));
+                        }
+                    }
+                    None => {
+                        post_order[node] = next;
+                        next += 1;
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        // Children come first in the topological sort so their labels
+        // are ready by the time the parent is visited
+        let mut labels = vec![(0, 0); n_slots];
+        let mut intervals: Vec<(u32, u32)> = Vec::new();
+        let mut ranges = Vec::new();
+        for (node, slot) in slots.iter().enumerate() {
+            if slot.is_none() {
+                continue;
+            }
+            ranges.clear();
+            ranges.push((subtree_start[node], post_order[node]));
+            for &child in &children[node] {
+                let (start, end) = labels[child as usize];
+                ranges.extend_from_slice(&intervals[start..end]);
+            }
+            ranges.sort_unstable();
+
+            let start = intervals.len();
+            for &(low, high) in &ranges {
+                if intervals.len() > start {
+                    let last = intervals.last_mut().expect("Label is not empty");
+                    if low <= last.1.saturating_add(1) {
+                        last.1 = last.1.max(high);
+                        continue;
+                    }
+                }
+                intervals.push((low, high));
+            }
+            labels[node] = (start, intervals.len());
+            if intervals.len() > max_intervals {
+                return None;
+            }
+        }
+
+        Some(ReachabilityIndex {
+            post_order,
+            labels,
+            intervals,
+        })
+    }
+
+    fn reaches(&self, from: u32, to: u32) -> bool {
+        let (start, end) = self.labels[from as usize];
+        let ranges = &self.intervals[start..end];
+        let target = self.post_order[to as usize];
+        let after = ranges.partition_point(|&(low, _)| low <= target);
+        after > 0 && ranges[after - 1].1 >= target
+    }
+}
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Precomputes which nodes are reachable from every node so that
+    /// [`DirectedAcyclicGraph::is_reachable`] answers with a binary
+    /// search instead of a traversal.
+    ///
+    /// The index stores ranges of nodes, one per node for a tree and a
+    /// few for graphs close to one. Densely cross-linked graphs can need
+    /// up to `n_nodes / 2` ranges per node, so the index is not built
+    /// once it grows past 64 ranges per node on average. Returns whether
+    /// the index was built; when it was not, `is_reachable` keeps using
+    /// a traversal. The index is dropped whenever the graph is mutated.
+    pub fn build_reachability_index(&mut self) -> bool {
+        let max_intervals = MAX_INTERVALS_PER_NODE * self.n_nodes();
+        self.reachability = ReachabilityIndex::build(self, max_intervals);
+        self.reachability.is_some()
+    }
+
+    pub fn has_reachability_index(&self) -> bool {
+        self.reachability.is_some()
+    }
+
+    pub fn drop_reachability_index(&mut self) -> &mut Self {
+        self.reachability = None;
+        self
+    }
+
+    /// Whether there is a path from `from` to `to`. A node is always
+    /// reachable from itself.
+    ///
+    /// Uses the reachability index if it was built, otherwise it falls
+    /// back to a traversal that skips every node placed before `to` in
+    /// the topological sort.
+    pub fn is_reachable(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<bool> {
+        let from_id = self.get_node_id(&from)?;
+        let to_id = self.get_node_id(&to)?;
+        if from_id == to_id {
+            return Ok(true);
+        }
+
+        let from_index = self.topological_index(&from_id);
+        let to_index = self.topological_index(&to_id);
+        if to_index > from_index {
+            return Ok(false);
+        }
+
+        if let Some(reachability) = &self.reachability {
+            return Ok(reachability.reaches(from_index as u32, to_index as u32));
+        }
+
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![&from_id];
+        while let Some(node_id) = to_visit.pop() {
+            for child in self.children(node_id)? {
+                if child == &to_id {
+                    return Ok(true);
+                }
+                if self.topological_index(child) > to_index && visited.insert(child) {
+                    to_visit.push(child);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diamond_dag() -> DirectedAcyclicGraph<()> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_node("3", ());
+        let _ = graph.add_node("4", ());
+        let _ = graph.add_path(&["0", "1", "3"]);
+        let _ = graph.add_path(&["0", "2", "3"]);
+        DirectedAcyclicGraph::build(graph).unwrap()
+    }
+
+    fn assert_reachability(graph: &DirectedAcyclicGraph<()>) {
+        assert!(graph.is_reachable("0", "3").unwrap());
+        assert!(graph.is_reachable("1", "3").unwrap());
+        assert!(graph.is_reachable("2", "2").unwrap());
+        assert!(!graph.is_reachable("3", "0").unwrap());
+        assert!(!graph.is_reachable("1", "2").unwrap());
+        assert!(!graph.is_reachable("0", "4").unwrap());
+        assert!(graph.is_reachable("0", "5").is_err());
+    }
+
+    #[test]
+    fn test_is_reachable_without_index() {
+        let graph = diamond_dag();
+        assert!(!graph.has_reachability_index());
+        assert_reachability(&graph);
+    }
+
+    #[test]
+    fn test_is_reachable_with_index() {
+        let mut graph = diamond_dag();
+        assert!(graph.build_reachability_index());
+        assert!(graph.has_reachability_index());
+        assert_reachability(&graph);
+    }
+
+    #[test]
+    fn test_reachability_index_matches_traversal() {
+        let mut graph = DirectedGraph::<()>::new();
+        for i in 0..40 {
+            let _ = graph.add_node(i.to_string(), ());
+        }
+        for i in 0..40 {
+            for step in [1, 3, 7] {
+                if (i * 5 + step) % 4 != 0 && i + step < 40 {
+                    let _ = graph.add_edge(i.to_string(), (i + step).to_string());
+                }
+            }
+        }
+        let without_index = DirectedAcyclicGraph::build(graph).unwrap();
+        let mut with_index = without_index.clone();
+        assert!(with_index.build_reachability_index());
+
+        for from in 0..40 {
+            for to in 0..40 {
+                let (from, to) = (from.to_string(), to.to_string());
+                assert_eq!(
+                    with_index.is_reachable(&from, &to).unwrap(),
+                    without_index.is_reachable(&from, &to).unwrap(),
+                    "{from} -> {to}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reachability_index_is_bounded() {
+        let graph = diamond_dag();
+
+        // Only `2` needs a second range, for the edge to `3` that is
+        // not part of the spanning tree
+        let labels = ReachabilityIndex::build(&graph, 100);
+        assert_eq!(labels.unwrap().intervals.len(), graph.n_nodes() + 1);
+        assert!(ReachabilityIndex::build(&graph, 2).is_none());
+    }
+
+    #[test]
+    fn test_build_reachability_index_reports_giving_up() {
+        // Edges between pseudo random pairs of the two halves, so the
+        // nodes of the upper half reach scattered sets of the lower one
+        let mut graph = DirectedGraph::<()>::new();
+        for i in 0..1500 {
+            let _ = graph.add_node(i.to_string(), ());
+        }
+        let mut state = 1_u32;
+        for upper in 0..1000 {
+            for lower in 1000..1500 {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                if state & 1 == 0 {
+                    let _ = graph.add_edge(upper.to_string(), lower.to_string());
+                }
+            }
+        }
+        let mut graph = DirectedAcyclicGraph::build(graph).unwrap();
+        assert!(!graph.build_reachability_index());
+        assert!(!graph.has_reachability_index());
+        let child = graph.children("0").unwrap().next().unwrap().clone();
+        assert!(graph.is_reachable("0", child).unwrap());
+    }
+
+    #[test]
+    fn test_reachability_index_is_dropped_on_mutation() {
+        let mut graph = diamond_dag();
+        assert!(graph.build_reachability_index());
+
+        graph.add_edge("3", "4").unwrap();
+        assert!(!graph.has_reachability_index());
+        assert!(graph.is_reachable("0", "4").unwrap());
+
+        assert!(graph.build_reachability_index());
+        assert!(graph.is_reachable("0", "4").unwrap());
+        graph.remove_edge("3", "4");
+        assert!(!graph.has_reachability_index());
+        assert!(!graph.is_reachable("0", "4").unwrap());
+    }
+}
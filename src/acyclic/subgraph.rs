@@ -0,0 +1,116 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+
+impl<Data: Clone, EdgeData: Clone> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Any subgraph of a DAG is acyclic and the cached order restricted
+    /// to its nodes is still a topological sort, so there is no need
+    /// to sort again
+    fn restrict(&self, nodes: HashSet<NodeId>) -> Self {
+        let dg = self.dg.induced_subgraph(&nodes);
+        let topological_sort = self
+            .topological_sort
+            .iter()
+            .filter(|node_id| nodes.contains(*node_id))
+            .cloned()
+            .collect::<Vec<_>>()
+            .into();
+        DirectedAcyclicGraph {
+            dg: Box::new(dg),
+            topological_sort,
+            reachability: None,
+        }
+    }
+
+    /// See [`DirectedGraph::subgraph`]
+    pub fn subgraph(&self, nodes: &[impl AsRef<str>]) -> GraphInteractionResult<Self> {
+        Ok(self.restrict(self.subgraph_nodes(nodes)?))
+    }
+
+    /// See [`DirectedGraph::ego_graph`]
+    pub fn ego_graph(
+        &self,
+        node: impl AsRef<str>,
+        radius: usize,
+        direction: Direction,
+    ) -> GraphInteractionResult<Self> {
+        Ok(self.restrict(self.ego_nodes(node, radius, direction)?))
+    }
+
+    /// See [`DirectedGraph::subgraph_between`]
+    pub fn subgraph_between(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<Self> {
+        Ok(self.restrict(self.nodes_between(from, to)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dag() -> DirectedAcyclicGraph<u32> {
+        let mut graph = DirectedGraph::new();
+        for (node, data) in [("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)] {
+            let _ = graph.add_node(node, data);
+        }
+        let _ = graph.add_path(&["a", "b", "d"]);
+        let _ = graph.add_path(&["a", "c", "d", "e"]);
+        DirectedAcyclicGraph::build(graph).unwrap()
+    }
+
+    fn is_restricted(
+        subgraph: &DirectedAcyclicGraph<u32>,
+        dag: &DirectedAcyclicGraph<u32>,
+    ) -> bool {
+        let expected = dag
+            .topological_sort
+            .iter()
+            .filter(|node_id| subgraph.get_node(node_id).is_ok())
+            .collect::<Vec<_>>();
+        subgraph.topological_sort.iter().collect::<Vec<_>>() == expected
+            && subgraph
+                .topological_sort
+                .iter()
+                .enumerate()
+                .all(|(position, node_id)| subgraph.topological_index(node_id) == position)
+    }
+
+    #[test]
+    fn test_dag_subgraph() {
+        let dag = dag();
+
+        let subgraph = dag.subgraph(&["a", "d", "c"]).unwrap();
+
+        assert_eq!(subgraph.n_nodes(), 3);
+        assert!(subgraph.edge_exists("a", "c"));
+        assert!(!subgraph.edge_exists("a", "d"));
+        assert!(is_restricted(&subgraph, &dag));
+        assert_eq!(
+            subgraph.find_path("a", "d").unwrap(),
+            Some(vec!["a".into(), "c".into(), "d".into()])
+        );
+    }
+
+    #[test]
+    fn test_dag_ego_graph_and_subgraph_between() {
+        let dag = dag();
+
+        let ego = dag.ego_graph("d", 1, Direction::Incoming).unwrap();
+        assert_eq!(ego.get_leaves(), vec!["d"]);
+        assert_eq!(ego.n_nodes(), 3);
+        assert!(is_restricted(&ego, &dag));
+
+        let between = dag.subgraph_between("b", "e").unwrap();
+        assert_eq!(
+            between.topological_sort,
+            vec![NodeId::from("e"), "d".into(), "b".into()].into()
+        );
+        assert!(is_restricted(&between, &dag));
+
+        let mut between = dag.subgraph_between("a", "d").unwrap();
+        assert!(between.add_edge("d", "a").is_err());
+        assert!(between.add_edge("b", "c").is_ok());
+    }
+}
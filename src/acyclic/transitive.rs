@@ -0,0 +1,196 @@
+use crate::prelude::*;
+use std::cmp::Reverse;
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Walks the descendants of every node with a depth first search,
+    /// calling `visit` once with the node, each descendant and whether
+    /// the descendant is a child that is not reachable through another
+    /// child. Children are tried closest first in the topological sort,
+    /// so any child reachable from another one was already visited.
+    /// Only one visited marker per node is kept across all the walks.
+    fn walk_descendants(&self, mut visit: impl FnMut(u32, u32, bool)) {
+        let dg = &self.dg;
+        let mut visited_from = vec![u32::MAX; dg.n_nodes()];
+        let mut to_visit = Vec::new();
+
+        for node in 0..dg.n_nodes() as u32 {
+            let mut children = dg.children_at(node).to_vec();
+            children
+                .sort_unstable_by_key(|&child| Reverse(self.topological_index(dg.id_at(child))));
+
+            for child in children {
+                if visited_from[child as usize] == node {
+                    continue;
+                }
+                visited_from[child as usize] = node;
+                to_visit.push(child);
+                visit(node, child, true);
+                while let Some(current) = to_visit.pop() {
+                    for &next in dg.children_at(current) {
+                        if visited_from[next as usize] != node {
+                            visited_from[next as usize] = node;
+                            to_visit.push(next);
+                            visit(node, next, false);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn with_edges(&self, edges: DirectedGraph<Data, EdgeData>) -> Self {
+        DirectedAcyclicGraph {
+            dg: Box::new(edges),
+            topological_sort: self.topological_sort.clone(),
+            reachability: None,
+        }
+    }
+
+    /// Returns a new graph with the minimum set of edges that keeps the
+    /// same reachability between nodes. The topological sort is reused
+    /// since it remains valid.
+    pub fn transitive_reduction(&self) -> Self
+    where
+        Data: Clone,
+        EdgeData: Clone,
+    {
+        let mut dg = self.dg.cloned_nodes();
+        self.walk_descendants(|parent, child, is_direct| {
+            if is_direct {
+                let data = self
+                    .dg
+                    .edge_data_at(parent, child)
+                    .expect("Edge must exist");
+                dg.insert_edge(parent, child, data.clone());
+            }
+        });
+        self.with_edges(dg)
+    }
+
+    /// Returns a new graph where every node has an edge to every node
+    /// reachable from it. Existing edges keep their data and new edges
+    /// get the default edge data.
+    pub fn transitive_closure(&self) -> Self
+    where
+        Data: Clone,
+        EdgeData: Clone + Default,
+    {
+        let mut dg = self.dg.cloned_nodes();
+        self.walk_descendants(|node, descendant, _| {
+            let data = self
+                .dg
+                .edge_data_at(node, descendant)
+                .cloned()
+                .unwrap_or_default();
+            dg.insert_edge(node, descendant, data);
+        });
+        self.with_edges(dg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redundant_dag() -> DirectedAcyclicGraph<(), u32> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_node("3", ());
+        let _ = graph.add_edge_with("0", "1", 1);
+        let _ = graph.add_edge_with("1", "2", 2);
+        let _ = graph.add_edge_with("2", "3", 3);
+        let _ = graph.add_edge_with("0", "2", 4);
+        let _ = graph.add_edge_with("0", "3", 5);
+        DirectedAcyclicGraph::build(graph).unwrap()
+    }
+
+    fn sorted_edges<Data, EdgeData>(
+        graph: &DirectedAcyclicGraph<Data, EdgeData>,
+    ) -> Vec<(NodeId, NodeId)> {
+        let mut edges = graph
+            .edges()
+            .map(|edge| (edge.from(), edge.to()))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn test_transitive_reduction() {
+        let graph = redundant_dag();
+
+        let reduced = graph.transitive_reduction();
+
+        assert_eq!(
+            sorted_edges(&reduced),
+            vec![
+                ("0".into(), "1".into()),
+                ("1".into(), "2".into()),
+                ("2".into(), "3".into())
+            ]
+        );
+        assert_eq!(**reduced.get_edge("1", "2").unwrap().data(), 2);
+        assert_eq!(reduced.n_nodes(), 4);
+    }
+
+    #[test]
+    fn test_transitive_reduction_is_minimal() {
+        let mut graph = DirectedGraph::<()>::new();
+        for i in 0..30 {
+            let _ = graph.add_node(i.to_string(), ());
+        }
+        for i in 0..30 {
+            for step in [1, 2, 3, 5] {
+                if (i + step) % 3 != 0 && i + step < 30 {
+                    let _ = graph.add_edge(i.to_string(), (i + step).to_string());
+                }
+            }
+        }
+        let graph = DirectedAcyclicGraph::build(graph).unwrap();
+
+        let reduced = graph.transitive_reduction();
+
+        for from in 0..30 {
+            for to in 0..30 {
+                let (from, to) = (from.to_string(), to.to_string());
+                assert_eq!(
+                    reduced.is_reachable(&from, &to).unwrap(),
+                    graph.is_reachable(&from, &to).unwrap()
+                );
+            }
+        }
+        for (from, to) in sorted_edges(&reduced) {
+            let mut without_edge = reduced.clone();
+            without_edge.remove_edge(&from, &to);
+            assert!(!without_edge.is_reachable(&from, &to).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_transitive_closure() {
+        let graph = redundant_dag();
+
+        let closure = graph.transitive_closure();
+
+        assert_eq!(
+            sorted_edges(&closure),
+            vec![
+                ("0".into(), "1".into()),
+                ("0".into(), "2".into()),
+                ("0".into(), "3".into()),
+                ("1".into(), "2".into()),
+                ("1".into(), "3".into()),
+                ("2".into(), "3".into())
+            ]
+        );
+        assert_eq!(**closure.get_edge("0", "3").unwrap().data(), 5);
+        assert_eq!(**closure.get_edge("1", "3").unwrap().data(), 0);
+
+        assert_eq!(
+            sorted_edges(&closure.transitive_reduction()),
+            sorted_edges(&graph.transitive_reduction())
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::prelude::*;
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Checks the inner graph with [`DirectedGraph::validate`] and that
+    /// the cached topological sort holds every node once, with every
+    /// child placed before its parents. Like the inner check it only
+    /// exists with debug assertions.
+    #[cfg(debug_assertions)]
+    pub fn validate(&self) -> GraphInteractionResult<()> {
+        self.dg.validate()?;
+
+        let sorted = self.topological_sort.iter().count();
+        if sorted != self.dg.n_nodes() || self.topological_sort.len() != self.dg.n_nodes() {
+            return Err(InvalidGraph::TopologicalSortSize {
+                sorted,
+                nodes: self.dg.n_nodes(),
+            }
+            .into());
+        }
+        for (position, slot) in self.topological_sort.slots().iter().enumerate() {
+            if let Some(node_id) = slot {
+                if self.topological_sort.position(node_id) != Some(position) {
+                    return Err(InvalidGraph::StalePosition(node_id.clone()).into());
+                }
+            }
+        }
+        for node_id in self.dg.ids.ids() {
+            self.validate_order_of(node_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the node is in the topological sort after its children
+    /// and before its parents
+    pub(crate) fn validate_order_of(&self, node_id: &NodeId) -> GraphInteractionResult<()> {
+        let position = |node_id: &NodeId| {
+            self.topological_sort
+                .position(node_id)
+                .ok_or_else(|| InvalidGraph::StalePosition(node_id.clone()))
+        };
+        let node_position = position(node_id)?;
+        if self.topological_sort.slots()[node_position].as_ref() != Some(node_id) {
+            return Err(InvalidGraph::StalePosition(node_id.clone()).into());
+        }
+        for child in self.dg.neighbors_of(node_id, Direction::Outgoing) {
+            if position(child)? >= node_position {
+                return Err(InvalidGraph::EdgeAgainstOrder(node_id.clone(), child.clone()).into());
+            }
+        }
+        for parent in self.dg.neighbors_of(node_id, Direction::Incoming) {
+            if position(parent)? <= node_position {
+                return Err(InvalidGraph::EdgeAgainstOrder(parent.clone(), node_id.clone()).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use super::*;
+    use crate::testing::{check_against_model, mutations};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_dag_validate_detects_bad_order() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("a", ());
+        let _ = graph.add_node("b", ());
+        let _ = graph.add_edge("a", "b");
+        let mut dag = DirectedAcyclicGraph::build(graph).unwrap();
+        assert!(dag.validate().is_ok());
+
+        dag.topological_sort.place("a".into(), 0);
+        assert!(matches!(
+            dag.validate(),
+            Err(OrbweaverError::Invalid(InvalidGraph::StalePosition(_)))
+        ));
+
+        let mut dag = DirectedAcyclicGraph::build(dag.into_inner()).unwrap();
+        dag.topological_sort = vec!["a".into(), "b".into()].into();
+        assert!(matches!(
+            dag.validate(),
+            Err(OrbweaverError::Invalid(InvalidGraph::EdgeAgainstOrder(from, to))) if from == "a" && to == "b"
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn test_random_dag_mutations_keep_graph_consistent(ops in mutations()) {
+            let dag = DirectedAcyclicGraph::build(DirectedGraph::new()).unwrap();
+            check_against_model(dag, true, &ops)?;
+        }
+    }
+}
@@ -0,0 +1,548 @@
+//! Traversal and path algorithms written against the traits in
+//! [`crate::visit`], usable with any graph implementing them.
+//!
+//! The inherent methods of [`DirectedGraph`] call into this module.
+//! Its [`Visitable`] implementation tracks visited nodes over the dense
+//! node indices, which is where the graph specialises these algorithms.
+use crate::directed::{construct_path, Traversal, WeightedPath};
+use crate::prelude::*;
+use crate::visit::{Direction, GraphBase, IntoNeighbors, IntoNodeIdentifiers, VisitMap, Visitable};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::ops::Add;
+
+fn get_node_id<G: GraphBase + ?Sized>(
+    graph: &G,
+    id: impl AsRef<str>,
+) -> GraphInteractionResult<NodeId> {
+    graph
+        .node_id(id.as_ref())
+        .cloned()
+        .ok_or_else(|| OrbweaverError::node_not_exists(id))
+}
+
+fn node<'a, G: GraphBase>(graph: &'a G, id: &NodeId) -> Node<&'a G::Data> {
+    Node::new(id.clone(), graph.node_data(id).expect("Node must exist"))
+}
+
+/// Breadth-first traversal from every one of `nodes` following
+/// `direction`
+pub fn traverse<'a, G: IntoNeighbors + Visitable>(
+    graph: &'a G,
+    nodes: &[impl AsRef<str>],
+    direction: Direction,
+) -> GraphInteractionResult<Traversal<'a, G>> {
+    let start = nodes
+        .iter()
+        .map(|node| get_node_id(graph, node))
+        .collect::<GraphInteractionResult<Vec<_>>>()?;
+    Ok(Traversal::new(graph, direction, start))
+}
+
+/// Finds path using breadth-first search, the path found has the
+/// fewest edges
+pub fn find_path<G: IntoNeighbors + Visitable>(
+    graph: &G,
+    from: impl AsRef<str>,
+    to: impl AsRef<str>,
+) -> GraphInteractionResult<Option<Vec<NodeId>>> {
+    let start_id = get_node_id(graph, from)?;
+    let goal_id = get_node_id(graph, to)?;
+
+    if start_id == goal_id {
+        return Ok(Some(vec![start_id]));
+    }
+
+    let mut visited = graph.visit_map();
+    visited.visit(&start_id);
+    let mut prev = HashMap::new();
+    let mut queue = VecDeque::from([start_id.clone()]);
+
+    while let Some(current) = queue.pop_front() {
+        for child in graph.neighbors(&current) {
+            if !visited.visit(child) {
+                continue;
+            }
+            prev.insert(child.clone(), current.clone());
+            if *child == goal_id {
+                return Ok(Some(construct_path(&prev, &start_id, &goal_id)));
+            }
+            queue.push_back(child.clone());
+        }
+    }
+
+    Ok(None)
+}
+
+/// Every path from `from` to `to` that does not repeat a node
+pub fn find_all_paths<G: IntoNeighbors>(
+    graph: &G,
+    from: impl AsRef<str>,
+    to: impl AsRef<str>,
+) -> GraphInteractionResult<Vec<Vec<NodeId>>> {
+    let start_id = get_node_id(graph, from)?;
+    let goal_id = get_node_id(graph, to)?;
+
+    let mut all_paths = Vec::new();
+    all_paths_dfs(graph, start_id, &goal_id, &mut Vec::new(), &mut all_paths);
+    Ok(all_paths)
+}
+
+/// Depth-first search collecting every path from `current` to
+/// `goal_id` that extends `current_path`
+pub(crate) fn all_paths_dfs<G: IntoNeighbors>(
+    graph: &G,
+    current: NodeId,
+    goal_id: &NodeId,
+    current_path: &mut Vec<NodeId>,
+    all_paths: &mut Vec<Vec<NodeId>>,
+) {
+    // Add current node to path
+    current_path.push(current.clone());
+
+    // Check if the current node is the goal
+    if current == *goal_id {
+        all_paths.push(current_path.clone());
+    } else {
+        // Continue to next nodes that can be visited from the current
+        // node, skipping the ones already in the path in case of cycles
+        for child in graph.neighbors(&current) {
+            if !current_path.contains(child) {
+                all_paths_dfs(graph, child.clone(), goal_id, current_path, all_paths);
+            }
+        }
+    }
+
+    // Backtrack to explore another path
+    current_path.pop();
+}
+
+/// Nodes without children, sorted by id
+pub fn get_leaves<G: IntoNeighbors + IntoNodeIdentifiers>(graph: &G) -> Vec<NodeId> {
+    let mut leaves = graph
+        .node_identifiers()
+        .filter(|node_id| graph.neighbors(node_id).next().is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    leaves.sort_unstable();
+    leaves
+}
+
+/// Nodes without children reachable from any of `nodes`
+pub fn get_leaves_under<G: IntoNeighbors + Visitable>(
+    graph: &G,
+    nodes: &[impl AsRef<str>],
+) -> GraphInteractionResult<Vec<NodeId>> {
+    // Ids borrowed from the graph let its visit map skip hashing them
+    let mut to_visit = nodes
+        .iter()
+        .map(|node| {
+            graph
+                .node_id(node.as_ref())
+                .ok_or_else(|| OrbweaverError::node_not_exists(node))
+        })
+        .collect::<GraphInteractionResult<Vec<_>>>()?;
+    let mut visited = graph.visit_map();
+    let mut leaves = Vec::new();
+
+    while let Some(node_id) = to_visit.pop() {
+        if !visited.visit(node_id) {
+            continue;
+        }
+        let mut children = graph.neighbors(node_id).peekable();
+        if children.peek().is_none() {
+            leaves.push(node_id.clone());
+            continue;
+        }
+        to_visit.extend(children);
+    }
+
+    Ok(leaves)
+}
+
+/// Position of every node in [`IntoNodeIdentifiers::node_identifiers`],
+/// hashed only for graphs without [`IntoNodeIdentifiers::node_index`]
+struct Positions<'a, G> {
+    graph: &'a G,
+    hashed: HashMap<&'a NodeId, usize>,
+}
+
+impl<'a, G: IntoNodeIdentifiers> Positions<'a, G> {
+    fn new(graph: &'a G, ids: &[&'a NodeId]) -> Self {
+        let is_dense = ids.first().is_none_or(|id| graph.node_index(id).is_some());
+        let hashed = match is_dense {
+            true => HashMap::new(),
+            false => ids
+                .iter()
+                .enumerate()
+                .map(|(position, &id)| (id, position))
+                .collect(),
+        };
+        Positions { graph, hashed }
+    }
+
+    fn get(&self, id: &NodeId) -> Option<usize> {
+        self.graph
+            .node_index(id)
+            .or_else(|| self.hashed.get(id).copied())
+    }
+}
+
+/// Kahn's algorithm removing sinks first, like
+/// [`DirectedAcyclicGraph::build`]. The result starts with the sinks of
+/// the graph and ends with its sources.
+pub fn topological_sort<G: IntoNeighbors + IntoNodeIdentifiers>(
+    graph: &G,
+) -> GraphInteractionResult<Vec<NodeId>> {
+    let ids = graph.node_identifiers().collect::<Vec<_>>();
+    let positions = Positions::new(graph, &ids);
+    let mut remaining = ids
+        .iter()
+        .map(|node_id| graph.neighbors(node_id).count())
+        .collect::<Vec<_>>();
+    let mut no_deps = ids
+        .iter()
+        .zip(&remaining)
+        .filter(|(_, &children)| children == 0)
+        .map(|(&node_id, _)| node_id)
+        .collect::<Vec<_>>();
+    // Sorted so the result does not depend on the order of the nodes
+    no_deps.sort_unstable();
+    let mut res = Vec::with_capacity(ids.len());
+
+    while let Some(node_id) = no_deps.pop() {
+        res.push(node_id.clone());
+        let mut parents = graph
+            .neighbors_directed(node_id, Direction::Incoming)
+            .collect::<Vec<_>>();
+        parents.sort_unstable();
+        for parent in parents {
+            if let Some(position) = positions.get(parent) {
+                remaining[position] -= 1;
+                if remaining[position] == 0 {
+                    no_deps.push(parent);
+                }
+            }
+        }
+    }
+
+    if res.len() != ids.len() {
+        let is_unsorted =
+            |node_id: &NodeId| positions.get(node_id).is_some_and(|p| remaining[p] != 0);
+        let mut unsorted = ids
+            .iter()
+            .filter(|&&node_id| is_unsorted(node_id))
+            .map(|&node_id| node_id.clone())
+            .collect::<Vec<_>>();
+        unsorted.sort_unstable();
+        let cycle = find_cycle(graph, is_unsorted, &unsorted[0]);
+        return Err(GraphHasCycle { cycle, unsorted }.into());
+    }
+
+    Ok(res)
+}
+
+/// Walks the unsorted nodes left by Kahn's algorithm. Every one of
+/// them has at least one unsorted child so the walk must eventually
+/// revisit a node.
+fn find_cycle<G: IntoNeighbors>(
+    graph: &G,
+    is_unsorted: impl Fn(&NodeId) -> bool,
+    start: &NodeId,
+) -> Vec<NodeId> {
+    let mut walk = Vec::new();
+    let mut position = HashMap::new();
+    let mut current = start.clone();
+
+    while !position.contains_key(&current) {
+        position.insert(current.clone(), walk.len());
+        walk.push(current.clone());
+        let mut children = graph
+            .neighbors(&current)
+            .filter(|&child| is_unsorted(child))
+            .collect::<Vec<_>>();
+        children.sort_unstable();
+        current = children
+            .first()
+            .map(|&child| child.clone())
+            .expect("Unsorted nodes always have unsorted children");
+    }
+
+    walk.split_off(position[&current])
+}
+
+/// Heap entry ordered so that `BinaryHeap` pops the smallest cost first
+struct MinCost<W> {
+    cost: W,
+    node_id: NodeId,
+}
+
+impl<W: PartialOrd> PartialEq for MinCost<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: PartialOrd> Eq for MinCost<W> {}
+
+impl<W: PartialOrd> PartialOrd for MinCost<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: PartialOrd> Ord for MinCost<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.node_id.cmp(&self.node_id))
+    }
+}
+
+/// Finds the cheapest path using Dijkstra's algorithm.
+///
+/// The cost of the edge between two nodes is computed by `weight`,
+/// which must be non-negative.
+pub fn shortest_path_by<G, W, F>(
+    graph: &G,
+    from: impl AsRef<str>,
+    to: impl AsRef<str>,
+    mut weight: F,
+) -> GraphInteractionResult<Option<WeightedPath<W>>>
+where
+    G: IntoNeighbors + Visitable,
+    W: Copy + PartialOrd + Add<Output = W> + Default,
+    F: FnMut(Node<&G::Data>, Node<&G::Data>) -> W,
+{
+    let start_id = get_node_id(graph, from)?;
+    let goal_id = get_node_id(graph, to)?;
+
+    let mut dist: HashMap<NodeId, W> = HashMap::new();
+    let mut prev: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut settled = graph.visit_map();
+    let mut queue = BinaryHeap::new();
+
+    dist.insert(start_id.clone(), W::default());
+    queue.push(MinCost {
+        cost: W::default(),
+        node_id: start_id.clone(),
+    });
+
+    while let Some(MinCost { cost, node_id }) = queue.pop() {
+        if !settled.visit(&node_id) {
+            continue;
+        }
+
+        if node_id == goal_id {
+            let path = construct_path(&prev, &start_id, &goal_id);
+            return Ok(Some(WeightedPath::new(path, cost)));
+        }
+
+        for child in graph.neighbors(&node_id) {
+            if settled.is_visited(child) {
+                continue;
+            }
+            let next_cost = cost + weight(node(graph, &node_id), node(graph, child));
+            let is_shorter = match dist.get(child) {
+                Some(current) => next_cost < *current,
+                None => true,
+            };
+            if is_shorter {
+                dist.insert(child.clone(), next_cost);
+                prev.insert(child.clone(), node_id.clone());
+                queue.push(MinCost {
+                    cost: next_cost,
+                    node_id: child.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Finds the cheapest path using the Bellman-Ford algorithm.
+///
+/// Unlike [`shortest_path_by`] negative weights are allowed. If a
+/// negative cycle is reachable from `from` the
+/// [`OrbweaverError::NegativeCycle`] error is returned with the
+/// nodes that form the cycle.
+pub fn shortest_path_bellman_ford_by<G, W, F>(
+    graph: &G,
+    from: impl AsRef<str>,
+    to: impl AsRef<str>,
+    mut weight: F,
+) -> GraphInteractionResult<Option<WeightedPath<W>>>
+where
+    G: IntoNeighbors + IntoNodeIdentifiers,
+    W: Copy + PartialOrd + Add<Output = W> + Default,
+    F: FnMut(Node<&G::Data>, Node<&G::Data>) -> W,
+{
+    let start_id = get_node_id(graph, from)?;
+    let goal_id = get_node_id(graph, to)?;
+    let n_nodes = graph.node_count();
+
+    // Weights are computed once since the closure may be expensive
+    let edges = graph
+        .node_identifiers()
+        .flat_map(|parent| graph.neighbors(parent).map(move |child| (parent, child)))
+        .map(|(parent, child)| {
+            let w = weight(node(graph, parent), node(graph, child));
+            (parent, child, w)
+        })
+        .collect::<Vec<_>>();
+
+    let mut dist: HashMap<NodeId, W> = HashMap::new();
+    let mut prev: HashMap<NodeId, NodeId> = HashMap::new();
+    dist.insert(start_id.clone(), W::default());
+
+    let relax = |dist: &mut HashMap<NodeId, W>, prev: &mut HashMap<NodeId, NodeId>| {
+        let mut last_relaxed = None;
+        for (parent, child, w) in &edges {
+            let Some(&parent_cost) = dist.get(*parent) else {
+                continue;
+            };
+            let next_cost = parent_cost + *w;
+            let is_shorter = match dist.get(*child) {
+                Some(current) => next_cost < *current,
+                None => true,
+            };
+            if is_shorter {
+                dist.insert((*child).clone(), next_cost);
+                prev.insert((*child).clone(), (*parent).clone());
+                last_relaxed = Some((*child).clone());
+            }
+        }
+        last_relaxed
+    };
+
+    for _ in 1..n_nodes {
+        if relax(&mut dist, &mut prev).is_none() {
+            break;
+        }
+    }
+
+    if let Some(relaxed) = relax(&mut dist, &mut prev) {
+        // Walking back `n_nodes` steps guarantees we land inside the cycle
+        let mut current = relaxed;
+        for _ in 0..n_nodes {
+            current = prev
+                .get(&current)
+                .expect("Relaxed node has a predecessor")
+                .clone();
+        }
+        let mut cycle = vec![current.clone()];
+        let mut node_id = prev.get(&current).expect("Node is part of a cycle");
+        while node_id != &current {
+            cycle.push(node_id.clone());
+            node_id = prev.get(node_id).expect("Node is part of a cycle");
+        }
+        cycle.reverse();
+        return Err(OrbweaverError::NegativeCycle(cycle));
+    }
+
+    match dist.get(&goal_id) {
+        Some(&cost) => {
+            let path = construct_path(&prev, &start_id, &goal_id);
+            Ok(Some(WeightedPath::new(path, cost)))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> DirectedGraph<u32> {
+        let mut graph = DirectedGraph::new();
+        for (node, data) in [("0", 0), ("1", 1), ("2", 5), ("3", 1), ("4", 1)] {
+            let _ = graph.add_node(node, data);
+        }
+        let _ = graph.add_path(&["0", "1", "3", "4"]);
+        let _ = graph.add_path(&["0", "2", "4"]);
+        graph
+    }
+
+    #[test]
+    fn test_generic_algorithms_match_inherent_methods() {
+        let graph = graph();
+        let dag = DirectedAcyclicGraph::build(graph.clone()).unwrap();
+        let wrapped = Graph::from(graph.clone());
+
+        assert_eq!(
+            find_path(&graph, "0", "4").unwrap(),
+            graph.find_path("0", "4").unwrap()
+        );
+        assert_eq!(find_path(&dag, "4", "0").unwrap(), None);
+        assert_eq!(get_leaves(&wrapped), graph.get_leaves());
+        assert_eq!(
+            get_leaves_under(&dag, &["1"]).unwrap(),
+            graph.get_leaves_under(&["1"]).unwrap()
+        );
+        assert!(topological_sort(&graph)
+            .unwrap()
+            .iter()
+            .eq(dag.topological_sort.iter()));
+        let mut paths = find_all_paths(&wrapped, "0", "4").unwrap();
+        paths.sort_unstable();
+        assert_eq!(paths, vec![vec!["0", "1", "3", "4"], vec!["0", "2", "4"]]);
+        assert!(find_path(&graph, "0", "5").is_err());
+    }
+
+    #[test]
+    fn test_generic_algorithms_on_cycles() {
+        let mut graph = graph();
+        let _ = graph.add_edge("4", "1");
+
+        let Err(OrbweaverError::Cycle(err)) = topological_sort(&graph) else {
+            panic!("Expected a cycle");
+        };
+        let Err(OrbweaverError::Cycle(expected)) = DirectedAcyclicGraph::build(graph.clone())
+        else {
+            panic!("Expected a cycle");
+        };
+        assert_eq!(err.cycle, vec!["1", "3", "4"]);
+        assert_eq!(err.cycle, expected.cycle);
+        assert_eq!(err.unsorted, expected.unsorted);
+        assert_eq!(find_all_paths(&graph, "1", "4").unwrap().len(), 1);
+        assert_eq!(
+            traverse(&graph, &["4"], Direction::Outgoing)
+                .unwrap()
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_generic_shortest_path_by() {
+        let wrapped = Graph::from(graph());
+
+        let path = shortest_path_by(&wrapped, "0", "4", |_, to| **to.data())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(path.path(), &["0", "1", "3", "4"]);
+        assert_eq!(*path.cost(), 3);
+    }
+
+    #[test]
+    fn test_generic_bellman_ford() {
+        let graph = graph();
+        let reversed = graph.reversed();
+
+        let path =
+            shortest_path_bellman_ford_by(&reversed, "4", "0", |from, _| -i64::from(**from.data()))
+                .unwrap()
+                .unwrap();
+        assert_eq!(path.path(), &["4", "2", "0"]);
+        assert_eq!(*path.cost(), -6);
+
+        let mut cyclic = graph.clone();
+        let _ = cyclic.add_edge("4", "1");
+        let err = shortest_path_bellman_ford_by(&Graph::from(cyclic), "0", "4", |_, _| -1);
+        assert!(matches!(err, Err(OrbweaverError::NegativeCycle(_))));
+    }
+}
@@ -0,0 +1,549 @@
+//! Compact binary format for caching graphs on disk.
+//!
+//! Every node id is stored once in a string table and edges refer to
+//! nodes by their position in it. The layout, all integers little
+//! endian, is:
+//!
+//! | Field        | Size                                   |
+//! |--------------|----------------------------------------|
+//! | Magic        | 4 bytes, `ORBW`                        |
+//! | Version      | `u16`, currently [`FORMAT_VERSION`]    |
+//! | Kind         | `u8`, `0` directed, `1` acyclic        |
+//! | Reserved     | `u8`, always `0`                       |
+//! | Nodes        | `u64`                                  |
+//! | Edges        | `u64`                                  |
+//! | Payload size | `u64`                                  |
+//! | Checksum     | `u64`, FNV-1a of the payload           |
+//! | Payload      | Node ids, node data and edges          |
+//!
+//! Inside the payload lengths and node indices are LEB128 varints and
+//! node and edge data is written by [`BinaryData`].
+use crate::prelude::*;
+use std::io::{Read, Write};
+
+/// Version written in the header of new files
+pub const FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 4] = b"ORBW";
+const HEADER_LEN: usize = 40;
+const KIND_DIRECTED: u8 = 0;
+const KIND_ACYCLIC: u8 = 1;
+
+fn kind_name(kind: u8) -> &'static str {
+    match kind {
+        KIND_DIRECTED => "directed graph",
+        _ => "directed acyclic graph",
+    }
+}
+
+/// Encodes node or edge data in the binary format. Every value is
+/// length prefixed, so `decode` receives exactly the bytes written by
+/// `encode`.
+pub trait BinaryData: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+    fn decode(bytes: &[u8]) -> Result<Self, String>;
+}
+
+impl BinaryData for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        match bytes.is_empty() {
+            true => Ok(()),
+            false => Err(format!("Expected no bytes, found {}", bytes.len())),
+        }
+    }
+}
+
+impl BinaryData for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        String::from_utf8(bytes.to_vec()).map_err(|err| err.to_string())
+    }
+}
+
+impl BinaryData for Vec<u8> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl BinaryData for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err("Invalid boolean".to_string()),
+        }
+    }
+}
+
+macro_rules! impl_binary_data_for_number {
+    ($($number:ty),*) => {
+        $(
+            impl BinaryData for $number {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+                fn decode(bytes: &[u8]) -> Result<Self, String> {
+                    bytes
+                        .try_into()
+                        .map(<$number>::from_le_bytes)
+                        .map_err(|_| format!(
+                            "Expected {} bytes for `{}`, found {}",
+                            std::mem::size_of::<$number>(),
+                            stringify!($number),
+                            bytes.len()
+                        ))
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_data_for_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+#[derive(Debug)]
+pub enum BinaryError {
+    Io(std::io::Error),
+    /// The input does not start with the `ORBW` magic bytes
+    NotOrbweaver,
+    UnsupportedVersion(u16),
+    /// The header has an unknown graph kind or a non-zero reserved byte
+    InvalidHeader(String),
+    /// The file holds another kind of graph than the one requested
+    KindMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    ChecksumMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// The payload does not match the header
+    Corrupted(String),
+    /// [`BinaryData::decode`] rejected the data of a node or edge
+    Data(String),
+}
+
+impl std::fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Unable to read graph: {err}"),
+            Self::NotOrbweaver => write!(f, "Input is not an orbweaver binary graph"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported format version {version}, expected 1 to {FORMAT_VERSION}"
+            ),
+            Self::InvalidHeader(message) => write!(f, "Invalid header: {message}"),
+            Self::KindMismatch { expected, found } => {
+                write!(f, "Expected a {expected}, found a {found}")
+            }
+            Self::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Checksum mismatch, expected {expected:#018x}, found {found:#018x}"
+            ),
+            Self::Corrupted(message) => write!(f, "Corrupted graph: {message}"),
+            Self::Data(message) => write!(f, "Invalid node or edge data: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BinaryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for BinaryError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn write_data(out: &mut Vec<u8>, data: &impl BinaryData, scratch: &mut Vec<u8>) {
+    scratch.clear();
+    data.encode(scratch);
+    write_bytes(out, scratch);
+}
+
+/// Reads the payload. The checksum already matched, so anything
+/// unexpected is reported as corruption
+struct Cursor<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    fn varint(&mut self) -> Result<u64, BinaryError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self
+                .bytes
+                .split_first()
+                .ok_or_else(|| BinaryError::Corrupted("Unexpected end of payload".to_string()))?;
+            self.bytes = rest;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BinaryError::Corrupted("Varint is too long".to_string()))
+    }
+
+    fn index(&mut self, len: usize) -> Result<usize, BinaryError> {
+        let index = self.varint()?;
+        match index < len as u64 {
+            true => Ok(index as usize),
+            false => Err(BinaryError::Corrupted(format!(
+                "Node index {index} is out of bounds"
+            ))),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], BinaryError> {
+        let len = self.varint()?;
+        if len > self.bytes.len() as u64 {
+            return Err(BinaryError::Corrupted(
+                "Unexpected end of payload".to_string(),
+            ));
+        }
+        let (bytes, rest) = self.bytes.split_at(len as usize);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn data<T: BinaryData>(&mut self) -> Result<T, BinaryError> {
+        T::decode(self.bytes()?).map_err(BinaryError::Data)
+    }
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData>
+where
+    Data: BinaryData,
+    EdgeData: BinaryData,
+{
+    fn write_binary(&self, kind: u8, mut writer: impl Write) -> std::io::Result<()> {
+        // Nodes are written sorted by id so the output is reproducible
+        let mut nodes = (0..self.n_nodes() as u32).collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|&node| self.id_at(node));
+        let mut positions = vec![0u64; nodes.len()];
+        for (position, &node) in nodes.iter().enumerate() {
+            positions[node as usize] = position as u64;
+        }
+
+        let mut payload = Vec::new();
+        let mut scratch = Vec::new();
+        for &node in &nodes {
+            write_bytes(&mut payload, self.id_at(node).as_bytes());
+        }
+        for &node in &nodes {
+            write_data(&mut payload, self.data_at(node), &mut scratch);
+        }
+        let mut n_edges = 0u64;
+        for &from in &nodes {
+            let mut children = self
+                .children_at(from)
+                .iter()
+                .zip(&self.edge_data[from as usize])
+                .collect::<Vec<_>>();
+            children.sort_unstable_by_key(|(&to, _)| positions[to as usize]);
+            for (&to, data) in children {
+                write_varint(&mut payload, positions[from as usize]);
+                write_varint(&mut payload, positions[to as usize]);
+                write_data(&mut payload, data, &mut scratch);
+                n_edges += 1;
+            }
+        }
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        header.push(kind);
+        header.push(0);
+        header.extend_from_slice(&(nodes.len() as u64).to_le_bytes());
+        header.extend_from_slice(&n_edges.to_le_bytes());
+        header.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        header.extend_from_slice(&checksum(&payload).to_le_bytes());
+
+        writer.write_all(&header)?;
+        writer.write_all(&payload)?;
+        writer.flush()
+    }
+
+    /// Writes the graph in the compact binary format described in
+    /// [`crate::binary`]. Output is deterministic for a given graph.
+    pub fn write_to(&self, writer: impl Write) -> std::io::Result<()> {
+        self.write_binary(KIND_DIRECTED, writer)
+    }
+
+    /// Reads a graph written by [`DirectedGraph::write_to`]
+    pub fn read_from(reader: impl Read) -> Result<Self, OrbweaverError> {
+        Ok(Self::read_binary(KIND_DIRECTED, reader)?)
+    }
+
+    fn read_binary(kind: u8, mut reader: impl Read) -> Result<Self, BinaryError> {
+        let mut header = [0u8; HEADER_LEN];
+        reader
+            .read_exact(&mut header)
+            .map_err(|err| match err.kind() {
+                std::io::ErrorKind::UnexpectedEof => BinaryError::NotOrbweaver,
+                _ => BinaryError::Io(err),
+            })?;
+        if &header[0..4] != MAGIC {
+            return Err(BinaryError::NotOrbweaver);
+        }
+        let u64_at = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version == 0 || version > FORMAT_VERSION {
+            return Err(BinaryError::UnsupportedVersion(version));
+        }
+        if !matches!(header[6], KIND_DIRECTED | KIND_ACYCLIC) {
+            return Err(BinaryError::InvalidHeader(format!(
+                "Unknown graph kind {}",
+                header[6]
+            )));
+        }
+        if header[7] != 0 {
+            return Err(BinaryError::InvalidHeader(
+                "Reserved byte is not zero".to_string(),
+            ));
+        }
+        if header[6] != kind {
+            return Err(BinaryError::KindMismatch {
+                expected: kind_name(kind),
+                found: kind_name(header[6]),
+            });
+        }
+        let n_nodes = u64_at(8);
+        let n_edges = u64_at(16);
+        let payload_len = u64_at(24);
+        let expected = u64_at(32);
+
+        let mut payload = Vec::new();
+        reader.take(payload_len).read_to_end(&mut payload)?;
+        if payload.len() as u64 != payload_len {
+            return Err(BinaryError::Corrupted(
+                "Payload is shorter than declared in the header".to_string(),
+            ));
+        }
+        let found = checksum(&payload);
+        if found != expected {
+            return Err(BinaryError::ChecksumMismatch { expected, found });
+        }
+
+        // Every node takes at least two bytes of payload, which bounds
+        // the allocation even if the header lies
+        if n_nodes > payload_len {
+            return Err(BinaryError::Corrupted(
+                "More nodes than bytes in the payload".to_string(),
+            ));
+        }
+        let mut cursor = Cursor { bytes: &payload };
+        let mut node_ids = Vec::with_capacity(n_nodes as usize);
+        for _ in 0..n_nodes {
+            let id = std::str::from_utf8(cursor.bytes()?)
+                .map_err(|_| BinaryError::Corrupted("Node id is not UTF-8".to_string()))?;
+            node_ids.push(NodeId::from(id));
+        }
+
+        let mut graph = DirectedGraph::new();
+        for node_id in &node_ids {
+            let data = cursor.data::<Data>()?;
+            if graph.add_node(node_id, data).is_err() {
+                return Err(BinaryError::Corrupted(format!(
+                    "Duplicate node `{node_id}`"
+                )));
+            }
+        }
+        for _ in 0..n_edges {
+            let from = &node_ids[cursor.index(node_ids.len())?];
+            let to = &node_ids[cursor.index(node_ids.len())?];
+            let data = cursor.data::<EdgeData>()?;
+            if graph.edge_exists(from, to) {
+                return Err(BinaryError::Corrupted(format!(
+                    "Duplicate edge `{from}` -> `{to}`"
+                )));
+            }
+            graph
+                .add_edge_with(from, to, data)
+                .expect("Both nodes were added above");
+        }
+        if !cursor.bytes.is_empty() {
+            return Err(BinaryError::Corrupted(
+                "Trailing bytes after the last edge".to_string(),
+            ));
+        }
+
+        Ok(graph)
+    }
+}
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData>
+where
+    Data: BinaryData,
+    EdgeData: BinaryData,
+{
+    /// Writes the graph in the compact binary format, marked as acyclic
+    pub fn write_to(&self, writer: impl Write) -> std::io::Result<()> {
+        self.dg.write_binary(KIND_ACYCLIC, writer)
+    }
+
+    /// Reads a graph written by [`DirectedAcyclicGraph::write_to`] and
+    /// sorts it topologically
+    pub fn read_from(reader: impl Read) -> Result<Self, OrbweaverError> {
+        let dg = DirectedGraph::read_binary(KIND_ACYCLIC, reader)?;
+        DirectedAcyclicGraph::build(dg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> DirectedGraph<String, f64> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("a", "Alpha".to_string());
+        let _ = graph.add_node("b", "Beta".to_string());
+        let _ = graph.add_node("ñandú", String::new());
+        let _ = graph.add_edge_with("a", "b", 1.5);
+        let _ = graph.add_edge_with("a", "ñandú", -2.0);
+        let _ = graph.add_edge_with("b", "ñandú", 0.0);
+        graph
+    }
+
+    fn bytes(graph: &DirectedGraph<String, f64>) -> Vec<u8> {
+        let mut out = Vec::new();
+        graph.write_to(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let graph = graph();
+        let written = bytes(&graph);
+
+        let parsed = DirectedGraph::<String, f64>::read_from(written.as_slice()).unwrap();
+
+        assert_eq!(parsed.n_nodes(), 3);
+        assert_eq!(parsed.get_node("a").unwrap().data().as_str(), "Alpha");
+        assert_eq!(*parsed.get_edge("a", "ñandú").unwrap().data(), &-2.0);
+        assert_eq!(parsed.edges().count(), 3);
+        assert_eq!(bytes(&parsed), written);
+    }
+
+    #[test]
+    fn test_binary_round_trip_acyclic() {
+        let mut graph = DirectedGraph::<(), ()>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_path(&["0", "1", "2"]);
+        let dag = DirectedAcyclicGraph::build(graph).unwrap();
+
+        let mut out = Vec::new();
+        dag.write_to(&mut out).unwrap();
+        let parsed = DirectedAcyclicGraph::<(), ()>::read_from(out.as_slice()).unwrap();
+
+        assert_eq!(parsed.topological_sort, dag.topological_sort);
+    }
+
+    #[test]
+    fn test_binary_errors() {
+        let written = bytes(&graph());
+
+        assert!(matches!(
+            DirectedGraph::<String, f64>::read_from(&b"GRAPH"[..]),
+            Err(OrbweaverError::Binary(BinaryError::NotOrbweaver))
+        ));
+
+        let mut version = written.clone();
+        version[4] = 99;
+        assert!(matches!(
+            DirectedGraph::<String, f64>::read_from(version.as_slice()),
+            Err(OrbweaverError::Binary(BinaryError::UnsupportedVersion(99)))
+        ));
+        version[4] = 0;
+        assert!(matches!(
+            DirectedGraph::<String, f64>::read_from(version.as_slice()),
+            Err(OrbweaverError::Binary(BinaryError::UnsupportedVersion(0)))
+        ));
+
+        let mut kind = written.clone();
+        kind[6] = 7;
+        assert!(matches!(
+            DirectedGraph::<String, f64>::read_from(kind.as_slice()),
+            Err(OrbweaverError::Binary(BinaryError::InvalidHeader(_)))
+        ));
+        assert!(matches!(
+            DirectedAcyclicGraph::<String, f64>::read_from(written.as_slice()),
+            Err(OrbweaverError::Binary(BinaryError::KindMismatch {
+                expected: "directed acyclic graph",
+                found: "directed graph"
+            }))
+        ));
+
+        let mut reserved = written.clone();
+        reserved[7] = 1;
+        assert!(matches!(
+            DirectedGraph::<String, f64>::read_from(reserved.as_slice()),
+            Err(OrbweaverError::Binary(BinaryError::InvalidHeader(_)))
+        ));
+
+        let mut flipped = written.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            DirectedGraph::<String, f64>::read_from(flipped.as_slice()),
+            Err(OrbweaverError::Binary(BinaryError::ChecksumMismatch { .. }))
+        ));
+
+        assert!(matches!(
+            DirectedGraph::<String, f64>::read_from(&written[..written.len() - 1]),
+            Err(OrbweaverError::Binary(BinaryError::Corrupted(_)))
+        ));
+
+        assert!(matches!(
+            DirectedGraph::<String, u8>::read_from(written.as_slice()),
+            Err(OrbweaverError::Binary(BinaryError::Data(_)))
+        ));
+
+        let mut cyclic = DirectedGraph::<(), ()>::new();
+        let _ = cyclic.add_node("a", ());
+        let _ = cyclic.add_node("b", ());
+        let _ = cyclic.add_path(&["a", "b", "a"]);
+        let mut out = Vec::new();
+        cyclic.write_binary(KIND_ACYCLIC, &mut out).unwrap();
+        assert!(matches!(
+            DirectedAcyclicGraph::<(), ()>::read_from(out.as_slice()),
+            Err(OrbweaverError::Cycle(_))
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+
+const UNVISITED: usize = usize::MAX;
+
+struct TarjanState {
+    index: Vec<usize>,
+    low_link: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<u32>,
+    next_index: usize,
+}
+
+impl TarjanState {
+    fn visit(&mut self, node: u32) {
+        self.index[node as usize] = self.next_index;
+        self.low_link[node as usize] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node as usize] = true;
+    }
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Finds the strongly connected components of the graph using
+    /// Tarjan's algorithm.
+    ///
+    /// Every node belongs to exactly one component. The nodes in each
+    /// component are sorted and the components are returned in reverse
+    /// topological order, just like [`DirectedAcyclicGraph`] stores its
+    /// topological sort.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let n_nodes = self.n_nodes();
+        let mut roots = (0..n_nodes as u32).collect::<Vec<_>>();
+        roots.sort_unstable_by_key(|&node| self.id_at(node));
+
+        let mut state = TarjanState {
+            index: vec![UNVISITED; n_nodes],
+            low_link: vec![UNVISITED; n_nodes],
+            on_stack: vec![false; n_nodes],
+            stack: Vec::new(),
+            next_index: 0,
+        };
+        let mut components = Vec::new();
+
+        for root in roots {
+            if state.index[root as usize] != UNVISITED {
+                continue;
+            }
+
+            // Recursion is replaced by an explicit stack of the node being
+            // visited and the children it still has to explore
+            state.visit(root);
+            let mut call_stack = vec![(root, self.children_at(root).iter())];
+
+            while let Some((node, children)) = call_stack.last_mut() {
+                let node = *node as usize;
+                if let Some(&child) = children.next() {
+                    if state.index[child as usize] == UNVISITED {
+                        state.visit(child);
+                        call_stack.push((child, self.children_at(child).iter()));
+                    } else if state.on_stack[child as usize] {
+                        state.low_link[node] =
+                            state.low_link[node].min(state.index[child as usize]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    let parent = parent as usize;
+                    state.low_link[parent] = state.low_link[parent].min(state.low_link[node]);
+                }
+
+                if state.low_link[node] == state.index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = state.stack.pop() {
+                        state.on_stack[member as usize] = false;
+                        component.push(self.id_at(member).clone());
+                        if member as usize == node {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Collapses every strongly connected component into a single node.
+    ///
+    /// Each node of the resulting graph is named after the smallest
+    /// [`NodeId`] in its component and holds every member of the
+    /// component as its data.
+    pub fn condensation(&self) -> DirectedAcyclicGraph<Vec<NodeId>> {
+        let components = self.strongly_connected_components();
+
+        let mut component_of: HashMap<&NodeId, &NodeId> = HashMap::new();
+        let mut condensed = DirectedGraph::new();
+        for component in &components {
+            let name = &component[0];
+            for member in component {
+                component_of.insert(member, name);
+            }
+            condensed
+                .add_node(name, component.clone())
+                .expect("Components are disjoint");
+        }
+
+        for node_id in self.ids.ids() {
+            let parent = component_of[node_id];
+            for child in self.neighbors_of(node_id, Direction::Outgoing) {
+                let child = component_of[child];
+                if parent != child && !condensed.edge_exists(parent, child) {
+                    condensed
+                        .add_edge(parent, child)
+                        .expect("Components were added as nodes");
+                }
+            }
+        }
+
+        DirectedAcyclicGraph::build(condensed).expect("Condensation graph is always acyclic")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cyclic_graph() -> DirectedGraph<()> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("a", ());
+        let _ = graph.add_node("b", ());
+        let _ = graph.add_node("c", ());
+        let _ = graph.add_node("d", ());
+        let _ = graph.add_node("e", ());
+        let _ = graph.add_node("f", ());
+        let _ = graph.add_path(&["a", "b", "c", "a"]);
+        let _ = graph.add_path(&["c", "d", "e", "d"]);
+        let _ = graph.add_edge("e", "f");
+        graph
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let graph = cyclic_graph();
+
+        let components = graph.strongly_connected_components();
+
+        assert_eq!(
+            components,
+            vec![vec!["f"], vec!["d", "e"], vec!["a", "b", "c"]]
+        );
+    }
+
+    #[test]
+    fn test_strongly_connected_components_acyclic() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_path(&["0", "1", "2"]);
+
+        let components = graph.strongly_connected_components();
+
+        assert_eq!(components, vec![vec!["2"], vec!["1"], vec!["0"]]);
+    }
+
+    #[test]
+    fn test_condensation() {
+        let graph = cyclic_graph();
+
+        let condensed = graph.condensation();
+
+        assert_eq!(condensed.n_nodes(), 3);
+        assert_eq!(
+            *condensed.get_node("a").unwrap().data(),
+            &vec![NodeId::from("a"), NodeId::from("b"), NodeId::from("c")]
+        );
+        assert!(condensed.edge_exists("a", "d"));
+        assert!(condensed.edge_exists("d", "f"));
+        assert_eq!(
+            condensed.find_path("a", "f").unwrap().unwrap(),
+            vec!["a", "d", "f"]
+        );
+    }
+}
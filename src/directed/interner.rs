@@ -0,0 +1,94 @@
+//! Interning of the ids of a [`DirectedGraph`] into dense `u32` indices.
+//!
+//! Indices stay below the number of nodes: removing a node moves the
+//! last one into its slot, so per node state fits in a `Vec`.
+use crate::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interner {
+    ids: Vec<NodeId>,
+    indices: HashMap<NodeId, u32>,
+}
+
+impl Interner {
+    pub(crate) fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub(crate) fn ids(&self) -> &[NodeId] {
+        &self.ids
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<u32> {
+        self.indices.get(id).copied()
+    }
+
+    /// Index of `id` without hashing it when it is borrowed from the
+    /// interner, which is the case for every id handed out by the graph
+    pub(crate) fn get_borrowed(&self, id: &NodeId) -> Option<u32> {
+        let range = self.ids.as_ptr_range();
+        let address = id as *const NodeId;
+        if range.contains(&address) {
+            let offset = address as usize - range.start as usize;
+            return Some((offset / std::mem::size_of::<NodeId>()) as u32);
+        }
+        self.get(id)
+    }
+
+    pub(crate) fn id(&self, index: u32) -> &NodeId {
+        &self.ids[index as usize]
+    }
+
+    /// Interns a new id, the caller checks it is not interned already
+    pub(crate) fn insert(&mut self, id: NodeId) -> u32 {
+        let index = u32::try_from(self.ids.len()).expect("Graphs hold at most u32::MAX nodes");
+        self.indices.insert(id.clone(), index);
+        self.ids.push(id);
+        index
+    }
+
+    /// Removes the id at `index`, the last id takes its index
+    pub(crate) fn swap_remove(&mut self, index: u32) -> NodeId {
+        let id = self.ids.swap_remove(index as usize);
+        self.indices.remove(&id);
+        if let Some(moved) = self.ids.get(index as usize) {
+            self.indices.insert(moved.clone(), index);
+        }
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::default();
+        let a = interner.insert("a".into());
+        let b = interner.insert("b".into());
+        let c = interner.insert("c".into());
+
+        assert_eq!((a, b, c), (0, 1, 2));
+        assert_eq!(interner.get("b"), Some(1));
+        assert_eq!(interner.id(c), "c");
+
+        assert_eq!(interner.swap_remove(a), "a");
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get("a"), None);
+        assert_eq!(interner.get("c"), Some(0));
+        assert_eq!(interner.id(0), "c");
+    }
+
+    #[test]
+    fn test_get_borrowed() {
+        let mut interner = Interner::default();
+        let _ = interner.insert("a".into());
+        let _ = interner.insert("b".into());
+
+        assert_eq!(interner.get_borrowed(interner.id(1)), Some(1));
+        assert_eq!(interner.get_borrowed(&"a".into()), Some(0));
+        assert_eq!(interner.get_borrowed(&"z".into()), None);
+    }
+}
@@ -1,62 +1,410 @@
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
-use std::collections::{HashMap, HashSet};
-use std::ops::Not;
+use std::collections::HashSet;
+
+mod components;
+pub(crate) mod interner;
+mod neighbors;
+pub(crate) mod pagerank;
+mod shortest_path;
+mod subgraph;
+mod traversal;
+mod validate;
+use interner::Interner;
+pub use neighbors::Neighbors;
+pub(crate) use shortest_path::construct_path;
+pub use shortest_path::WeightedPath;
+pub use traversal::{Traversal, TraversalWithDepth};
+
+/// What [`DirectedGraph::add_edge_with_policy`] does when the edge
+/// already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnDuplicateEdge {
+    /// Replaces the data of the edge, like [`DirectedGraph::add_edge_with`]
+    #[default]
+    Replace,
+    /// Keeps the existing edge and its data
+    Ignore,
+    /// Fails with [`OrbweaverError::DuplicateEdge`]
+    Error,
+}
 
+/// Every node is interned into a dense `u32` index which is its
+/// position in the other fields. The string API looks the index up
+/// once and the algorithms work over the rows of indices directly.
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct DirectedGraph<Data> {
-    pub(crate) nodes: HashMap<NodeId, Data>,
-    pub(crate) parents: HashMap<NodeId, HashSet<NodeId>>,
-    pub(crate) children: HashMap<NodeId, HashSet<NodeId>>,
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "SerializedGraph<Data, EdgeData>",
+        bound(deserialize = "Data: Deserialize<'de>, EdgeData: Deserialize<'de>")
+    )
+)]
+pub struct DirectedGraph<Data, EdgeData = ()> {
+    pub(crate) ids: Interner,
+    pub(crate) data: Vec<Data>,
+    /// Children of every node, the data of each edge is at the same
+    /// position of `edge_data`
+    pub(crate) children: Vec<Vec<u32>>,
+    pub(crate) edge_data: Vec<Vec<EdgeData>>,
+    pub(crate) parents: Vec<Vec<u32>>,
     pub(crate) n_edges: usize,
 }
 
-impl<Data: Clone> Clone for DirectedGraph<Data> {
+/// Layout read by serde. Graphs written by 0.2.1, before edges carried
+/// data, have no `edges`. Their edges get the data read from a unit
+/// value, which is `()` for the edge data of that version. Parents and
+/// the edge count are rebuilt from the children.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct SerializedGraph<Data, EdgeData> {
+    nodes: std::collections::HashMap<NodeId, Data>,
+    #[allow(dead_code)]
+    parents: serde::de::IgnoredAny,
+    children: std::collections::HashMap<NodeId, HashSet<NodeId>>,
+    #[serde(default = "Option::default")]
+    edges: Option<std::collections::HashMap<NodeId, std::collections::HashMap<NodeId, EdgeData>>>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, Data, EdgeData: Deserialize<'de>> TryFrom<SerializedGraph<Data, EdgeData>>
+    for DirectedGraph<Data, EdgeData>
+{
+    type Error = serde::de::value::Error;
+    fn try_from(value: SerializedGraph<Data, EdgeData>) -> Result<Self, Self::Error> {
+        use serde::de::{Error, IntoDeserializer};
+
+        let SerializedGraph {
+            nodes,
+            children,
+            edges,
+            ..
+        } = value;
+        let legacy = edges.is_none();
+        let mut edges = edges.unwrap_or_default();
+
+        let mut nodes = nodes.into_iter().collect::<Vec<_>>();
+        nodes.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        let mut graph = DirectedGraph::new();
+        for (node_id, data) in nodes {
+            graph.insert_node(node_id, data);
+        }
+
+        let mut children = children.into_iter().collect::<Vec<_>>();
+        children.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        for (from, kept) in children {
+            let mut kept = kept.into_iter().collect::<Vec<_>>();
+            kept.sort_unstable();
+            for to in kept {
+                let (Some(from_index), Some(to_index)) =
+                    (graph.index_of(&from), graph.index_of(&to))
+                else {
+                    return Err(Error::custom(format_args!(
+                        "edge {from} -> {to} refers to a missing node"
+                    )));
+                };
+                let data = match edges.get_mut(&from).and_then(|data| data.remove(&to)) {
+                    Some(data) => data,
+                    None if legacy => EdgeData::deserialize(().into_deserializer())?,
+                    None => {
+                        return Err(Error::custom(format_args!(
+                            "edge {from} -> {to} has no data"
+                        )))
+                    }
+                };
+                graph.insert_edge(from_index, to_index, data);
+            }
+        }
+        Ok(graph)
+    }
+}
+
+/// Serializes the items built by the closure as a sequence
+#[cfg(feature = "serde")]
+struct SeqOf<F>(F);
+
+#[cfg(feature = "serde")]
+impl<F, I> Serialize for SeqOf<F>
+where
+    F: Fn() -> I,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq((self.0)())
+    }
+}
+
+/// Serializes the pairs built by the closure as a map
+#[cfg(feature = "serde")]
+struct MapOf<F>(F);
+
+#[cfg(feature = "serde")]
+impl<F, I, K, V> Serialize for MapOf<F>
+where
+    F: Fn() -> I,
+    I: IntoIterator<Item = (K, V)>,
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map((self.0)())
+    }
+}
+
+/// Written with the map based layout of earlier versions so they can
+/// still read it
+#[cfg(feature = "serde")]
+impl<Data: Serialize, EdgeData: Serialize> Serialize for DirectedGraph<Data, EdgeData> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        fn rows<'a>(ids: &'a Interner, rows: &'a [Vec<u32>]) -> impl Serialize + 'a {
+            MapOf(move || {
+                ids.ids().iter().zip(rows).map(move |(node_id, row)| {
+                    (node_id, SeqOf(move || row.iter().map(|&node| ids.id(node))))
+                })
+            })
+        }
+
+        let ids = &self.ids;
+        let edges = MapOf(|| {
+            ids.ids()
+                .iter()
+                .zip(self.children.iter().zip(&self.edge_data))
+                .filter(|(_, (children, _))| !children.is_empty())
+                .map(|(from, (children, data))| {
+                    let edges = MapOf(move || children.iter().map(|&to| ids.id(to)).zip(data));
+                    (from, edges)
+                })
+        });
+
+        let mut state = serializer.serialize_struct("DirectedGraph", 5)?;
+        state.serialize_field("nodes", &MapOf(|| ids.ids().iter().zip(&self.data)))?;
+        state.serialize_field("parents", &rows(ids, &self.parents))?;
+        state.serialize_field("children", &rows(ids, &self.children))?;
+        state.serialize_field("edges", &edges)?;
+        state.serialize_field("n_edges", &self.n_edges)?;
+        state.end()
+    }
+}
+
+impl<Data: Clone, EdgeData: Clone> Clone for DirectedGraph<Data, EdgeData> {
     fn clone(&self) -> Self {
         DirectedGraph {
-            nodes: HashMap::clone(&self.nodes),
-            parents: HashMap::clone(&self.parents),
-            children: HashMap::clone(&self.children),
+            ids: self.ids.clone(),
+            data: self.data.clone(),
+            children: self.children.clone(),
+            edge_data: self.edge_data.clone(),
+            parents: self.parents.clone(),
             n_edges: self.n_edges,
         }
     }
 }
 
-impl<Data> DirectedGraph<Data> {
+impl<Data, EdgeData: Default> DirectedGraph<Data, EdgeData> {
+    /// Adds an edge with the default edge data
+    pub fn add_edge(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<&mut Self> {
+        self.add_edge_with(from, to, EdgeData::default())
+    }
+
+    pub fn add_path(&mut self, path: &[impl AsRef<str>]) -> GraphInteractionResult<&mut Self> {
+        for edge in path.windows(2) {
+            let from = unsafe { edge.get_unchecked(0) };
+            let to = unsafe { edge.get_unchecked(1) };
+            self.add_edge(from, to)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Removes `node` from the row, which does not keep its order
+fn remove_from(row: &mut Vec<u32>, node: u32) -> Option<usize> {
+    let position = row.iter().position(|&other| other == node)?;
+    row.swap_remove(position);
+    Some(position)
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
     pub fn new() -> Self {
         DirectedGraph {
-            nodes: HashMap::new(),
-            parents: HashMap::new(),
-            children: HashMap::new(),
+            ids: Interner::default(),
+            data: Vec::new(),
+            children: Vec::new(),
+            edge_data: Vec::new(),
+            parents: Vec::new(),
             n_edges: 0,
         }
     }
     pub fn n_nodes(&self) -> usize {
-        self.nodes.len()
+        self.ids.len()
+    }
+    pub fn n_edges(&self) -> usize {
+        self.n_edges
+    }
+
+    pub(crate) fn index_of(&self, id: &str) -> Option<u32> {
+        self.ids.get(id)
+    }
+    fn require(&self, id: impl AsRef<str>) -> GraphInteractionResult<u32> {
+        self.index_of(id.as_ref())
+            .ok_or_else(|| OrbweaverError::node_not_exists(id))
+    }
+    pub(crate) fn id_at(&self, node: u32) -> &NodeId {
+        self.ids.id(node)
+    }
+    pub(crate) fn data_at(&self, node: u32) -> &Data {
+        &self.data[node as usize]
+    }
+    pub(crate) fn node_data_mut(&mut self, id: &str) -> Option<&mut Data> {
+        let node = self.index_of(id)?;
+        Some(&mut self.data[node as usize])
+    }
+    pub(crate) fn children_at(&self, node: u32) -> &[u32] {
+        &self.children[node as usize]
+    }
+    pub(crate) fn parents_at(&self, node: u32) -> &[u32] {
+        &self.parents[node as usize]
+    }
+    /// Position of the edge in the row of children of `from`
+    fn edge_position(&self, from: u32, to: u32) -> Option<usize> {
+        self.children[from as usize]
+            .iter()
+            .position(|&child| child == to)
+    }
+    pub(crate) fn edge_data_at(&self, from: u32, to: u32) -> Option<&EdgeData> {
+        self.edge_position(from, to)
+            .map(|position| &self.edge_data[from as usize][position])
+    }
+
+    /// Copy of the nodes without any edge, every node keeps its index
+    pub(crate) fn cloned_nodes(&self) -> DirectedGraph<Data, EdgeData>
+    where
+        Data: Clone,
+    {
+        DirectedGraph {
+            ids: self.ids.clone(),
+            data: self.data.clone(),
+            children: vec![Vec::new(); self.data.len()],
+            edge_data: std::iter::repeat_with(Vec::new)
+                .take(self.data.len())
+                .collect(),
+            parents: vec![Vec::new(); self.data.len()],
+            n_edges: 0,
+        }
+    }
+
+    pub(crate) fn insert_node(&mut self, node_id: NodeId, data: Data) -> u32 {
+        let node = self.ids.insert(node_id);
+        self.data.push(data);
+        self.children.push(Vec::new());
+        self.edge_data.push(Vec::new());
+        self.parents.push(Vec::new());
+        debug_assert!(self.validate_rows().is_ok() && self.validate_node(node).is_ok());
+        node
+    }
+
+    /// Adds the edge or replaces its data, returning the previous data
+    pub(crate) fn insert_edge(&mut self, from: u32, to: u32, data: EdgeData) -> Option<EdgeData> {
+        if let Some(position) = self.edge_position(from, to) {
+            let previous = std::mem::replace(&mut self.edge_data[from as usize][position], data);
+            return Some(previous);
+        }
+        self.children[from as usize].push(to);
+        self.edge_data[from as usize].push(data);
+        self.parents[to as usize].push(from);
+        self.n_edges += 1;
+        debug_assert!(
+            matches!(self.stored_edge(from, to), Ok(true)) && self.validate_edge_data(from).is_ok()
+        );
+        None
+    }
+
+    fn take_edge(&mut self, from: u32, to: u32) -> Option<EdgeData> {
+        let position = remove_from(&mut self.children[from as usize], to)?;
+        remove_from(&mut self.parents[to as usize], from);
+        self.n_edges -= 1;
+        let data = self.edge_data[from as usize].swap_remove(position);
+        debug_assert!(
+            matches!(self.stored_edge(from, to), Ok(false))
+                && self.validate_edge_data(from).is_ok()
+        );
+        Some(data)
     }
+
+    fn take_node(&mut self, node: u32) -> Data {
+        let children = std::mem::take(&mut self.children[node as usize]);
+        self.edge_data[node as usize].clear();
+        for &child in &children {
+            remove_from(&mut self.parents[child as usize], node);
+        }
+        self.n_edges -= children.len();
+        for parent in std::mem::take(&mut self.parents[node as usize]) {
+            if let Some(position) = remove_from(&mut self.children[parent as usize], node) {
+                self.edge_data[parent as usize].swap_remove(position);
+                self.n_edges -= 1;
+            }
+        }
+
+        // The last node takes the index of the removed one
+        let last = (self.ids.len() - 1) as u32;
+        self.ids.swap_remove(node);
+        self.children.swap_remove(node as usize);
+        self.edge_data.swap_remove(node as usize);
+        self.parents.swap_remove(node as usize);
+        let data = self.data.swap_remove(node as usize);
+        if node != last {
+            self.relabel(last, node);
+        }
+        debug_assert!(
+            self.validate_rows().is_ok() && (node == last || self.validate_node(node).is_ok())
+        );
+        data
+    }
+
+    /// Points the rows of the neighbors of the node now at `new` away
+    /// from its previous index `old`
+    fn relabel(&mut self, old: u32, new: u32) {
+        let rename = |row: &mut Vec<u32>| {
+            row.iter_mut()
+                .filter(|node| **node == old)
+                .for_each(|node| *node = new)
+        };
+        for position in 0..self.children[new as usize].len() {
+            let child = self.children[new as usize][position];
+            rename(&mut self.parents[if child == old { new } else { child } as usize]);
+        }
+        for position in 0..self.parents[new as usize].len() {
+            let parent = self.parents[new as usize][position];
+            rename(&mut self.children[if parent == old { new } else { parent } as usize]);
+        }
+    }
+
     pub fn add_node(
         &mut self,
         id: impl AsRef<str>,
         data: Data,
-    ) -> Result<&mut Self, DuplicateNode> {
-        let node_id: NodeId = id.as_ref().into();
-
-        match self.nodes.insert(node_id.clone(), data) {
-            Some(_) => Err(DuplicateNode(node_id)),
-            _ => {
-                self.children.insert(node_id.clone(), HashSet::new());
-                self.parents.insert(node_id.clone(), HashSet::new());
-                Ok(self)
-            }
+    ) -> GraphInteractionResult<&mut Self> {
+        if self.index_of(id.as_ref()).is_some() {
+            return Err(OrbweaverError::DuplicateNode(id.as_ref().into()));
         }
+        self.insert_node(id.as_ref().into(), data);
+        Ok(self)
     }
     pub fn get_node(&self, id: impl AsRef<str>) -> GraphInteractionResult<Node<&Data>> {
-        if let Some((node_id, data)) = self.nodes.get_key_value(id.as_ref()) {
-            return Ok(Node::new(node_id.clone(), data));
-        }
-        Err(GraphInteractionError::node_not_exists(id))
+        let node = self.require(id)?;
+        Ok(Node::new(self.id_at(node).clone(), self.data_at(node)))
+    }
+    pub(crate) fn node_unchecked(&self, id: &NodeId) -> Node<&Data> {
+        let node = self.ids.get_borrowed(id).expect("Node must exist");
+        Node::new(id.clone(), self.data_at(node))
     }
     pub fn get_nodes(
         &self,
@@ -65,101 +413,179 @@ impl<Data> DirectedGraph<Data> {
         ids.map(|node_id| self.get_node(node_id)).collect()
     }
     pub fn get_node_id(&self, id: impl AsRef<str>) -> GraphInteractionResult<NodeId> {
-        if let Some((node_id, _)) = self.nodes.get_key_value(id.as_ref()) {
-            return Ok(node_id.clone());
-        }
-        Err(GraphInteractionError::node_not_exists(id))
+        Ok(self.id_at(self.require(id)?).clone())
     }
-    pub fn add_edge(
+    /// Adds an edge carrying `data`. If the edge already exists its
+    /// data is replaced.
+    pub fn add_edge_with(
         &mut self,
         from: impl AsRef<str>,
         to: impl AsRef<str>,
+        data: EdgeData,
     ) -> GraphInteractionResult<&mut Self> {
-        let from = self.get_node_id(&from)?;
-        let to = self.get_node_id(&to)?;
-        self.children
-            .get_mut(&from)
-            .expect("Node must exist")
-            .insert(to.clone());
-        self.parents
-            .get_mut(&to)
-            .expect("Node must exist")
-            .insert(from.clone());
-        self.n_edges += 1;
+        let from = self.require(from)?;
+        let to = self.require(to)?;
+        self.insert_edge(from, to, data);
         Ok(self)
     }
 
-    pub fn add_path(&mut self, path: &[impl AsRef<str>]) -> GraphInteractionResult<&mut Self> {
-        for edge in path.windows(2) {
-            let from = unsafe { edge.get_unchecked(0) };
-            let to = unsafe { edge.get_unchecked(1) };
-            self.add_edge(from, to)?;
+    /// Adds an edge carrying `data`, handling an existing edge
+    /// according to `policy`
+    pub fn add_edge_with_policy(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        data: EdgeData,
+        policy: OnDuplicateEdge,
+    ) -> Result<&mut Self, OrbweaverError> {
+        if self.edge_exists(&from, &to) {
+            match policy {
+                OnDuplicateEdge::Replace => {}
+                OnDuplicateEdge::Ignore => return Ok(self),
+                OnDuplicateEdge::Error => {
+                    return Err(OrbweaverError::DuplicateEdge(
+                        self.get_node_id(from)?,
+                        self.get_node_id(to)?,
+                    ))
+                }
+            }
         }
-        Ok(self)
+        self.add_edge_with(from, to, data)
     }
 
-    pub fn edge_exists(&self, from: impl AsRef<str>, to: impl AsRef<str>) -> bool {
-        if let Some(children) = self.children.get(from.as_ref()) {
-            return children.contains(to.as_ref());
+    pub fn get_edge(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<Edge<&EdgeData>> {
+        let from_index = self.require(&from)?;
+        let to_index = self.require(&to)?;
+        match self.edge_data_at(from_index, to_index) {
+            Some(data) => Ok(Edge::new(
+                self.id_at(from_index).clone(),
+                self.id_at(to_index).clone(),
+                data,
+            )),
+            None => Err(OrbweaverError::edge_not_exists(from, to)),
         }
-        false
     }
 
-    pub fn children(&self, node: impl AsRef<str>) -> GraphInteractionResult<&HashSet<NodeId>> {
-        match self.children.get(node.as_ref()) {
-            None => Err(GraphInteractionError::node_not_exists(node)),
-            Some(children) => Ok(children),
+    pub fn edge_data_mut(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<&mut EdgeData> {
+        let from_index = self.require(&from)?;
+        let to_index = self.require(&to)?;
+        match self.edge_position(from_index, to_index) {
+            Some(position) => Ok(&mut self.edge_data[from_index as usize][position]),
+            None => Err(OrbweaverError::edge_not_exists(from, to)),
         }
     }
 
-    pub fn parents(&self, node: impl AsRef<str>) -> GraphInteractionResult<&HashSet<NodeId>> {
-        match self.parents.get(node.as_ref()) {
-            None => Err(GraphInteractionError::node_not_exists(node)),
-            Some(parents) => Ok(parents),
-        }
+    /// Edges leaving the node at `from`
+    fn row_edges(&self, from: u32) -> impl Iterator<Item = Edge<&EdgeData>> {
+        let from_id = self.id_at(from);
+        self.children[from as usize]
+            .iter()
+            .zip(&self.edge_data[from as usize])
+            .map(move |(&to, data)| Edge::new(from_id.clone(), self.id_at(to).clone(), data))
     }
 
-    pub fn remove_edge(&mut self, from: impl AsRef<str>, to: impl AsRef<str>) -> &mut Self {
-        let children = self.children.get_mut(from.as_ref());
-        let parents = self.parents.get_mut(to.as_ref());
+    /// Iterates over every edge in the graph
+    pub fn edges(&self) -> impl Iterator<Item = Edge<&EdgeData>> {
+        (0..self.ids.len() as u32).flat_map(|from| self.row_edges(from))
+    }
 
-        if let (Some(children), Some(parents)) = (children, parents) {
-            if children.remove(to.as_ref()) && parents.remove(from.as_ref()) {
-                self.n_edges -= 1;
-            }
-        }
+    /// Iterates over the edges leaving `node`
+    pub fn out_edges(
+        &self,
+        node: impl AsRef<str>,
+    ) -> GraphInteractionResult<impl Iterator<Item = Edge<&EdgeData>>> {
+        Ok(self.row_edges(self.require(node)?))
+    }
 
-        self
+    /// Iterates over the edges arriving at `node`
+    pub fn in_edges(
+        &self,
+        node: impl AsRef<str>,
+    ) -> GraphInteractionResult<impl Iterator<Item = Edge<&EdgeData>>> {
+        let to = self.require(node)?;
+        Ok(self.parents_at(to).iter().map(move |&from| {
+            let data = self.edge_data_at(from, to).expect("Edge must exist");
+            Edge::new(self.id_at(from).clone(), self.id_at(to).clone(), data)
+        }))
     }
 
-    pub fn remove_node(&mut self, node_id: impl AsRef<str>) -> &mut Self {
-        // Remove all edges that point to this
-        if let Some(parents) = self.parents.get(node_id.as_ref()) {
-            // We remove this node from the children list
-            for parent in parents {
-                if let Some(children) = self.children.get_mut(parent) {
-                    children.remove(node_id.as_ref());
-                }
+    pub fn edge_exists(&self, from: impl AsRef<str>, to: impl AsRef<str>) -> bool {
+        match (self.index_of(from.as_ref()), self.index_of(to.as_ref())) {
+            // Scans the shorter of the two rows
+            (Some(from), Some(to))
+                if self.parents[to as usize].len() < self.children[from as usize].len() =>
+            {
+                self.parents_at(to).contains(&from)
             }
-            self.parents.remove(node_id.as_ref());
+            (Some(from), Some(to)) => self.children_at(from).contains(&to),
+            _ => false,
         }
+    }
 
-        // Remove all edges from this node to other nodes
-        if let Some(children) = self.children.get(node_id.as_ref()) {
-            // We remove this node from other node's parent list
-            for child in children {
-                if let Some(parents) = self.parents.get_mut(child) {
-                    parents.remove(node_id.as_ref());
-                }
-            }
-            self.children.remove(node_id.as_ref());
+    pub fn children(&self, node: impl AsRef<str>) -> GraphInteractionResult<Neighbors<'_>> {
+        let node = self.require(node)?;
+        Ok(Neighbors::new(&self.ids, self.children_at(node)))
+    }
+
+    pub fn parents(&self, node: impl AsRef<str>) -> GraphInteractionResult<Neighbors<'_>> {
+        let node = self.require(node)?;
+        Ok(Neighbors::new(&self.ids, self.parents_at(node)))
+    }
+
+    /// Children or parents of `id`, empty if the node does not exist
+    pub(crate) fn neighbors_of(&self, id: &str, direction: Direction) -> Neighbors<'_> {
+        let rows = match direction {
+            Direction::Outgoing => &self.children,
+            Direction::Incoming => &self.parents,
+        };
+        match self.index_of(id) {
+            Some(node) => Neighbors::new(&self.ids, &rows[node as usize]),
+            None => Neighbors::empty(&self.ids),
         }
+    }
 
-        self.nodes.remove(node_id.as_ref());
+    pub fn remove_edge(&mut self, from: impl AsRef<str>, to: impl AsRef<str>) -> &mut Self {
+        if let (Some(from), Some(to)) = (self.index_of(from.as_ref()), self.index_of(to.as_ref())) {
+            self.take_edge(from, to);
+        }
+        self
+    }
 
+    pub fn remove_node(&mut self, node_id: impl AsRef<str>) -> &mut Self {
+        if let Some(node) = self.index_of(node_id.as_ref()) {
+            self.take_node(node);
+        }
         self
     }
 
+    /// Removes the edge returning its data, fails if either node or
+    /// the edge does not exist
+    pub fn try_remove_edge(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> Result<EdgeData, OrbweaverError> {
+        let from_index = self.require(&from)?;
+        let to_index = self.require(&to)?;
+        self.take_edge(from_index, to_index)
+            .ok_or_else(|| OrbweaverError::edge_not_exists(from, to))
+    }
+
+    /// Removes the node and all of its edges returning its data, fails
+    /// if the node does not exist
+    pub fn try_remove_node(&mut self, node_id: impl AsRef<str>) -> Result<Data, OrbweaverError> {
+        let node = self.require(node_id)?;
+        Ok(self.take_node(node))
+    }
+
     pub fn has_parents(&self, id: impl AsRef<str>) -> GraphInteractionResult<bool> {
         Ok(!self.parents(id)?.is_empty())
     }
@@ -169,120 +595,73 @@ impl<Data> DirectedGraph<Data> {
     }
 
     pub fn nodes(&self) -> impl Iterator<Item = Node<&Data>> {
-        self.nodes
+        self.ids
+            .ids()
             .iter()
+            .zip(&self.data)
             .map(|(node_id, data)| Node::new(node_id.clone(), data))
     }
 
     pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
-        self.nodes.keys().cloned()
+        self.ids.ids().iter().cloned()
     }
 
-    pub fn into_dataless(&self) -> DirectedGraph<()> {
-        let nodes = self
-            .nodes
-            .keys()
-            .map(|node_id| (node_id.clone(), ()))
-            .collect();
+    /// Copies the structure of the graph dropping both node and edge data
+    pub fn into_dataless(&self) -> DirectedGraph<(), ()> {
         DirectedGraph {
-            nodes,
-            parents: self.parents.clone(),
+            ids: self.ids.clone(),
+            data: vec![(); self.data.len()],
             children: self.children.clone(),
+            edge_data: self
+                .children
+                .iter()
+                .map(|row| vec![(); row.len()])
+                .collect(),
+            parents: self.parents.clone(),
             n_edges: self.n_edges,
         }
     }
-    /// Finds path using breadth-first search
+    /// Finds path using breadth-first search, the path found has the
+    /// fewest edges. See [`crate::algo::find_path`].
     pub fn find_path(
         &self,
         from: impl AsRef<str>,
         to: impl AsRef<str>,
     ) -> GraphInteractionResult<Option<Vec<NodeId>>> {
-        // Helper function for constructing the path
-        fn construct_path(
-            parents: &[(&NodeId, &NodeId)],
-            start_id: &NodeId,
-            goal_id: &NodeId,
-        ) -> Vec<NodeId> {
-            let mut path = Vec::new();
-            let mut current_id = goal_id;
-            path.push(current_id.clone());
-
-            while current_id != start_id {
-                if let Some(parent_pair) = parents.iter().find(|(node, _)| *node == current_id) {
-                    current_id = &parent_pair.1;
-                    path.push(current_id.clone());
-                } else {
-                    break; // This should not happen if the path exists
-                }
-            }
-
-            path.reverse(); // Reverse to get the path from start to goal
-            path
-        }
-
-        let start_id = self.get_node(&from)?.node_id;
-        let goal_id = self.get_node(&to)?.node_id;
-
-        if start_id == goal_id {
-            return Ok(Some(vec![start_id]));
-        }
-
-        let mut queue = Vec::new();
-        let mut visited = HashSet::new();
-        let mut parents = Vec::new(); // To track the path back to the start node
-
-        // Initialize
-        queue.push(&start_id);
-        visited.insert(&start_id);
-
-        while let Some(current) = queue.pop() {
-            for child in self.children(current)? {
-                if !visited.contains(&child) {
-                    visited.insert(child);
-                    parents.push((child, current));
-
-                    if child == &goal_id {
-                        // If goal found, construct the path from parents
-                        return Ok(Some(construct_path(&parents, &start_id, &goal_id)));
-                    }
-                    queue.push(child);
-                }
-            }
-        }
-
-        Ok(None)
+        crate::algo::find_path(self, from, to)
     }
 
     pub fn clear_edges(&mut self) -> &mut Self {
-        self.parents.clear();
-        self.children.clear();
+        // Every node keeps an empty row of parents and children
+        self.children.iter_mut().for_each(Vec::clear);
+        self.edge_data.iter_mut().for_each(Vec::clear);
+        self.parents.iter_mut().for_each(Vec::clear);
         self.n_edges = 0;
         self
     }
 
+    /// Nodes in `selected` that have no parent in `selected`. For the
+    /// lowest common ancestors in the graph see
+    /// [`DirectedAcyclicGraph::lowest_common_ancestors`].
     pub fn least_common_parents(
         &self,
         selected: &[impl AsRef<str>],
     ) -> GraphInteractionResult<Vec<NodeId>> {
-        let selected: HashSet<NodeId> = selected
+        let selected: HashSet<u32> = selected
             .iter()
-            .map(|node| self.get_node_id(node.as_ref()))
+            .map(|node| self.require(node))
             .collect::<GraphInteractionResult<_>>()?;
+        // A node without parents is part of the set of least common
+        // parents
         let mut least_common_parent = selected
             .iter()
-            .filter(|&node_id| {
-                match self.parents.get(node_id.as_ref()) {
-                    // We return true because if the node has no parent then
-                    // it is part of the set of least common
-                    // parents
-                    None => true,
-                    Some(parents) => parents
-                        .iter()
-                        .any(|parent| selected.contains(parent.as_ref()))
-                        .not(),
-                }
+            .filter(|&node| {
+                !self
+                    .parents_at(*node)
+                    .iter()
+                    .any(|parent| selected.contains(parent))
             })
-            .cloned()
+            .map(|&node| self.id_at(node).clone())
             .collect::<Vec<_>>();
 
         least_common_parent.sort_unstable();
@@ -293,12 +672,12 @@ impl<Data> DirectedGraph<Data> {
     /// With no dependencies
     pub fn get_leaves(&self) -> Vec<NodeId> {
         let mut leaves = self
-            .node_ids()
-            .filter(|node_id| {
-                !self
-                    .has_children(node_id)
-                    .expect("Using nodes from the graph directly")
-            })
+            .ids
+            .ids()
+            .iter()
+            .zip(&self.children)
+            .filter(|(_, children)| children.is_empty())
+            .map(|(node_id, _)| node_id.clone())
             .collect::<Vec<_>>();
 
         leaves.sort_unstable();
@@ -306,37 +685,44 @@ impl<Data> DirectedGraph<Data> {
         leaves
     }
 
-    /// Get leaves under a node or group of nodes
+    /// Get leaves under a node or group of nodes. See
+    /// [`crate::algo::get_leaves_under`].
     pub fn get_leaves_under(
         &self,
         nodes: &[impl AsRef<str>],
     ) -> GraphInteractionResult<Vec<NodeId>> {
-        let mut leaves = Vec::new();
-        let mut visited = HashSet::new();
-        let mut to_visit = nodes
-            .iter()
-            .map(|node| self.get_node_id(node.as_ref()))
-            .collect::<GraphInteractionResult<Vec<_>>>()?;
+        crate::algo::get_leaves_under(self, nodes)
+    }
 
-        while let Some(node) = to_visit.pop() {
-            if visited.contains(&node) {
-                continue;
-            }
-            visited.insert(node.clone());
-            if self.has_children(&node)?.not() {
-                leaves.push(node);
-                continue;
-            }
-            self.children(&node)?
-                .iter()
-                .for_each(|child| to_visit.push(child.clone()));
-        }
+    /// Leaves under every one of `roots`, computed separately for each
+    pub fn get_leaves_under_each(
+        &self,
+        roots: &[impl AsRef<str>],
+    ) -> GraphInteractionResult<Vec<Vec<NodeId>>> {
+        roots
+            .iter()
+            .map(|root| self.get_leaves_under(&[root]))
+            .collect()
+    }
 
-        Ok(leaves)
+    /// Computes `metric` for every node, sorted by node id
+    pub fn node_metrics<T>(&self, mut metric: impl FnMut(Node<&Data>) -> T) -> Vec<(NodeId, T)> {
+        let mut nodes = (0..self.ids.len() as u32).collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|&node| self.id_at(node));
+        nodes
+            .into_iter()
+            .map(|node| {
+                let node_id = self.id_at(node).clone();
+                (
+                    node_id.clone(),
+                    metric(Node::new(node_id, self.data_at(node))),
+                )
+            })
+            .collect()
     }
 }
 
-impl<Data> Default for DirectedGraph<Data> {
+impl<Data, EdgeData> Default for DirectedGraph<Data, EdgeData> {
     fn default() -> Self {
         Self::new()
     }
@@ -551,4 +937,237 @@ mod tests {
 
         assert_eq!(graph.get_leaves(), vec!["4", "5"]);
     }
+
+    #[test]
+    fn test_add_edge_with_data() {
+        let mut graph = DirectedGraph::<(), u32>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_edge_with("0", "1", 10);
+        let _ = graph.add_edge("1", "2");
+
+        assert_eq!(**graph.get_edge("0", "1").unwrap().data(), 10);
+        assert_eq!(**graph.get_edge("1", "2").unwrap().data(), 0);
+        assert!(graph.get_edge("0", "2").is_err());
+
+        *graph.edge_data_mut("1", "2").unwrap() = 5;
+        assert_eq!(**graph.get_edge("1", "2").unwrap().data(), 5);
+
+        let mut edges = graph
+            .edges()
+            .map(|edge| (edge.from(), edge.to(), **edge.data()))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        assert_eq!(
+            edges,
+            vec![
+                (NodeId::from("0"), NodeId::from("1"), 10),
+                (NodeId::from("1"), NodeId::from("2"), 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_in_and_out_edges() {
+        let mut graph = DirectedGraph::<(), &str>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_edge_with("0", "2", "a");
+        let _ = graph.add_edge_with("1", "2", "b");
+
+        let mut in_edges = graph
+            .in_edges("2")
+            .unwrap()
+            .map(|edge| **edge.data())
+            .collect::<Vec<_>>();
+        in_edges.sort_unstable();
+        assert_eq!(in_edges, vec!["a", "b"]);
+
+        let out_edges = graph
+            .out_edges("0")
+            .unwrap()
+            .map(|edge| edge.to())
+            .collect::<Vec<_>>();
+        assert_eq!(out_edges, vec!["2"]);
+        assert_eq!(graph.out_edges("2").unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_remove_edge_drops_edge_data() {
+        let mut graph = DirectedGraph::<(), u32>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_edge_with("0", "1", 1);
+        let _ = graph.add_edge_with("1", "2", 2);
+
+        graph.remove_edge("0", "1");
+        assert!(graph.get_edge("0", "1").is_err());
+
+        graph.remove_node("2");
+        assert_eq!(graph.edges().count(), 0);
+
+        let dataless = graph.into_dataless();
+        assert_eq!(dataless.edges().count(), 0);
+    }
+
+    #[test]
+    fn test_try_remove_edge() {
+        let mut graph = DirectedGraph::<(), u32>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_edge_with("0", "1", 7);
+
+        assert!(matches!(
+            graph.try_remove_edge("1", "0"),
+            Err(OrbweaverError::EdgeNotExist(..))
+        ));
+        assert!(matches!(
+            graph.try_remove_edge("0", "2"),
+            Err(OrbweaverError::NodeNotExist(..))
+        ));
+        assert_eq!(graph.try_remove_edge("0", "1").unwrap(), 7);
+        assert!(!graph.edge_exists("0", "1"));
+        assert!(graph.parents("1").unwrap().is_empty());
+        assert!(graph.try_remove_edge("0", "1").is_err());
+    }
+
+    #[test]
+    fn test_try_remove_node() {
+        let mut graph = DirectedGraph::<String>::new();
+        let _ = graph.add_node("0", "zero".to_string());
+        let _ = graph.add_node("1", "one".to_string());
+        let _ = graph.add_edge("0", "1");
+
+        assert_eq!(graph.try_remove_node("1").unwrap(), "one");
+        assert!(graph.get_node("1").is_err());
+        assert!(graph.children("0").unwrap().is_empty());
+        assert!(matches!(
+            graph.try_remove_node("1"),
+            Err(OrbweaverError::NodeNotExist(node_id)) if node_id == "1"
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_graph_without_edge_data() {
+        // Layout written by 0.2.1, before edges carried data. The stale
+        // edge count is recomputed.
+        let json = r#"{
+            "nodes": {"a": 1, "b": 2, "c": 3},
+            "parents": {"a": [], "b": ["a"], "c": ["a", "b"]},
+            "children": {"a": ["b", "c"], "b": ["c"], "c": []},
+            "n_edges": 4
+        }"#;
+
+        let graph: DirectedGraph<u32> = serde_json::from_str(json).unwrap();
+        assert_eq!(graph.edges().count(), 3);
+        let graph: DirectedGraph<u32, Option<u32>> = serde_json::from_str(json).unwrap();
+        assert_eq!(**graph.get_edge("a", "c").unwrap().data(), None);
+        assert!(serde_json::from_str::<DirectedGraph<u32, u32>>(json).is_err());
+
+        let dag: DirectedAcyclicGraph<u32> = serde_json::from_str(&format!(
+            r#"{{"dg": {json}, "topological_sort": ["c", "b", "a"]}}"#
+        ))
+        .unwrap();
+        assert!(dag.find_path("a", "c").unwrap().is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip_keeps_edge_data() {
+        let mut graph = DirectedGraph::<u32, String>::new();
+        let _ = graph.add_node("a", 1);
+        let _ = graph.add_node("b", 2);
+        let _ = graph.add_edge_with("a", "b", "ab".to_string());
+        let dag = DirectedAcyclicGraph::build(graph).unwrap();
+
+        let json = serde_json::to_string(&dag).unwrap();
+        let parsed: DirectedAcyclicGraph<u32, String> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(*parsed.get_edge("a", "b").unwrap().data(), "ab");
+        assert_eq!(parsed.topological_sort, dag.topological_sort);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_edge_data_without_default() {
+        use std::num::NonZeroU32;
+
+        let mut graph = DirectedGraph::<u32, NonZeroU32>::new();
+        let _ = graph.add_node("a", 1);
+        let _ = graph.add_node("b", 2);
+        let _ = graph.add_edge_with("a", "b", NonZeroU32::new(7).unwrap());
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let parsed: DirectedGraph<u32, NonZeroU32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.get_edge("a", "b").unwrap().data().get(), 7);
+
+        let missing = r#"{
+            "nodes": {"a": 1, "b": 2},
+            "parents": {"a": [], "b": ["a"]},
+            "children": {"a": ["b"], "b": []},
+            "edges": {}
+        }"#;
+        assert!(serde_json::from_str::<DirectedGraph<u32, NonZeroU32>>(missing).is_err());
+    }
+
+    #[test]
+    fn test_add_duplicate_node_keeps_data() {
+        let mut graph = DirectedGraph::<u32>::new();
+        let _ = graph.add_node("a", 1);
+        assert!(graph.add_node("a", 2).is_err());
+        assert_eq!(**graph.get_node("a").unwrap().data(), 1);
+
+        let mut dag = DirectedAcyclicGraph::build(graph).unwrap();
+        assert!(dag.add_node("a", 3).is_err());
+        assert_eq!(**dag.get_node("a").unwrap().data(), 1);
+    }
+
+    #[test]
+    fn test_remove_node_moves_last_node_into_its_index() {
+        let mut graph = DirectedGraph::<u32, u32>::new();
+        for (node, data) in [("a", 0), ("b", 1), ("c", 2), ("d", 3)] {
+            let _ = graph.add_node(node, data);
+        }
+        let _ = graph.add_edge_with("a", "d", 1);
+        let _ = graph.add_edge_with("d", "b", 2);
+        let _ = graph.add_edge_with("d", "d", 3);
+        let _ = graph.add_edge_with("b", "c", 4);
+
+        assert_eq!(graph.try_remove_node("a").unwrap(), 0);
+        assert_eq!(graph.n_edges(), 3);
+        assert_eq!(**graph.get_node("d").unwrap().data(), 3);
+        assert_eq!(**graph.get_edge("d", "b").unwrap().data(), 2);
+        assert_eq!(**graph.get_edge("d", "d").unwrap().data(), 3);
+        assert!(graph.parents("d").unwrap().contains("d"));
+        assert!(!graph.parents("d").unwrap().contains("a"));
+
+        graph.remove_node("d");
+        assert_eq!(graph.n_edges(), 1);
+        assert!(graph.edge_exists("b", "c"));
+    }
+
+    #[test]
+    fn test_children_and_parents() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("a", ());
+        let _ = graph.add_node("b", ());
+        let _ = graph.add_node("c", ());
+        let _ = graph.add_edge("a", "b");
+        let _ = graph.add_edge("a", "c");
+
+        let children = graph.children("a").unwrap();
+        assert_eq!(children.len(), 2);
+        assert!(children.contains("b"));
+        assert!(!children.contains("a"));
+        assert!(!children.contains("z"));
+        let mut children = children.collect::<Vec<_>>();
+        children.sort_unstable();
+        assert_eq!(children, vec!["b", "c"]);
+        assert!(graph.parents("a").unwrap().is_empty());
+        assert!(graph.children("z").is_err());
+    }
 }
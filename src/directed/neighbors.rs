@@ -0,0 +1,54 @@
+use super::interner::Interner;
+use crate::prelude::*;
+
+/// Children or parents of a node, borrowed from the graph
+#[derive(Debug, Clone)]
+pub struct Neighbors<'a> {
+    ids: &'a Interner,
+    row: std::slice::Iter<'a, u32>,
+}
+
+impl<'a> Neighbors<'a> {
+    pub(crate) fn new(ids: &'a Interner, row: &'a [u32]) -> Self {
+        Neighbors {
+            ids,
+            row: row.iter(),
+        }
+    }
+
+    pub(crate) fn empty(ids: &'a Interner) -> Self {
+        Neighbors::new(ids, &[])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.row.len() == 0
+    }
+
+    /// Whether `id` is one of the remaining neighbors, scanning them
+    pub fn contains(&self, id: impl AsRef<str>) -> bool {
+        match self.ids.get(id.as_ref()) {
+            Some(index) => self.row.as_slice().contains(&index),
+            None => false,
+        }
+    }
+}
+
+impl<'a> Iterator for Neighbors<'a> {
+    type Item = &'a NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.row.next().map(|&index| self.ids.id(index))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.row.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Neighbors<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.row.next_back().map(|&index| self.ids.id(index))
+    }
+}
+
+impl ExactSizeIterator for Neighbors<'_> {}
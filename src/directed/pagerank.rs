@@ -0,0 +1,110 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// Computes the new rank of every node given a function returning the
+/// rank of one node. Lets the parallel version share the iteration.
+pub(crate) type RankMap<'a> = &'a dyn Fn(&(dyn Fn(u32) -> f64 + Sync), usize) -> Vec<f64>;
+
+/// Power iteration over the dense indices. The rank of every node only
+/// depends on the previous iteration and is summed over its row of
+/// parents in order, so the result does not depend on how `map` splits
+/// the work.
+pub(crate) fn pagerank_iterations<Data, EdgeData>(
+    dg: &DirectedGraph<Data, EdgeData>,
+    damping: f64,
+    tolerance: f64,
+    max_iterations: usize,
+    map: RankMap<'_>,
+) -> HashMap<NodeId, f64> {
+    let n_nodes = dg.n_nodes();
+    if n_nodes == 0 {
+        return HashMap::new();
+    }
+    let out_degree = (0..n_nodes as u32)
+        .map(|node| dg.children_at(node).len() as f64)
+        .collect::<Vec<_>>();
+    let parents = &dg.parents;
+    let mut ranks = vec![1.0 / n_nodes as f64; n_nodes];
+
+    for _ in 0..max_iterations {
+        // Nodes without children spread their rank over every node
+        let dangling = (0..n_nodes)
+            .filter(|&node| out_degree[node] == 0.0)
+            .map(|node| ranks[node])
+            .sum::<f64>();
+        let base = (1.0 - damping) / n_nodes as f64 + damping * dangling / n_nodes as f64;
+        let previous = &ranks;
+        let next = map(
+            &|node| {
+                let node = node as usize;
+                let incoming = parents[node]
+                    .iter()
+                    .map(|&parent| previous[parent as usize] / out_degree[parent as usize])
+                    .sum::<f64>();
+                base + damping * incoming
+            },
+            n_nodes,
+        );
+        let change = next
+            .iter()
+            .zip(&ranks)
+            .map(|(next, previous)| (next - previous).abs())
+            .sum::<f64>();
+        ranks = next;
+        if change < tolerance {
+            break;
+        }
+    }
+
+    ranks
+        .into_iter()
+        .enumerate()
+        .map(|(node, rank)| (dg.id_at(node as u32).clone(), rank))
+        .collect()
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// PageRank of every node, following edges from parent to child.
+    /// Iterates until the ranks change by less than `tolerance`, summed
+    /// over every node, or `max_iterations` is reached. The ranks add
+    /// up to `1`.
+    pub fn pagerank(
+        &self,
+        damping: f64,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> HashMap<NodeId, f64> {
+        pagerank_iterations(
+            self,
+            damping,
+            tolerance,
+            max_iterations,
+            &|rank, n_nodes| (0..n_nodes as u32).map(rank).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagerank() {
+        let mut graph = DirectedGraph::<()>::new();
+        for node in ["a", "b", "c", "d"] {
+            let _ = graph.add_node(node, ());
+        }
+        let _ = graph.add_path(&["a", "b", "c", "a"]);
+        let _ = graph.add_edge("d", "c");
+
+        let ranks = graph.pagerank(0.85, 1e-12, 1000);
+
+        assert!((ranks.values().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(ranks["c"] > ranks["b"]);
+        assert!(ranks["b"] > ranks["d"]);
+        assert!((ranks["d"] - 0.15 / 4.0).abs() < 1e-9);
+        assert!(DirectedGraph::<()>::new()
+            .pagerank(0.85, 1e-6, 10)
+            .is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::ops::Add;
+
+/// A path through the graph together with its total cost
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedPath<W> {
+    path: Vec<NodeId>,
+    cost: W,
+}
+
+impl<W> WeightedPath<W> {
+    pub(crate) fn new(path: Vec<NodeId>, cost: W) -> Self {
+        WeightedPath { path, cost }
+    }
+    pub fn path(&self) -> &[NodeId] {
+        &self.path
+    }
+    pub fn cost(&self) -> &W {
+        &self.cost
+    }
+    pub fn into_parts(self) -> (Vec<NodeId>, W) {
+        (self.path, self.cost)
+    }
+}
+
+pub(crate) fn construct_path(
+    prev: &HashMap<NodeId, NodeId>,
+    start_id: &NodeId,
+    goal_id: &NodeId,
+) -> Vec<NodeId> {
+    let mut path = vec![goal_id.clone()];
+    let mut current_id = goal_id;
+    while current_id != start_id {
+        current_id = prev
+            .get(current_id)
+            .expect("Every reached node has a predecessor");
+        path.push(current_id.clone());
+    }
+    path.reverse();
+    path
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Finds the cheapest path using Dijkstra's algorithm.
+    ///
+    /// The cost of the edge between two nodes is computed by `weight`.
+    /// Weights must be non-negative, use
+    /// [`DirectedGraph::shortest_path_bellman_ford_by`] otherwise.
+    pub fn shortest_path_by<W, F>(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        weight: F,
+    ) -> GraphInteractionResult<Option<WeightedPath<W>>>
+    where
+        W: Copy + PartialOrd + Add<Output = W> + Default,
+        F: FnMut(Node<&Data>, Node<&Data>) -> W,
+    {
+        crate::algo::shortest_path_by(self, from, to, weight)
+    }
+
+    /// Finds the cheapest path using the Bellman-Ford algorithm.
+    ///
+    /// Unlike [`DirectedGraph::shortest_path_by`] negative weights are
+    /// allowed. If a negative cycle is reachable from `from` the
+    /// [`OrbweaverError::NegativeCycle`] error is returned with
+    /// the nodes that form the cycle.
+    pub fn shortest_path_bellman_ford_by<W, F>(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        weight: F,
+    ) -> GraphInteractionResult<Option<WeightedPath<W>>>
+    where
+        W: Copy + PartialOrd + Add<Output = W> + Default,
+        F: FnMut(Node<&Data>, Node<&Data>) -> W,
+    {
+        crate::algo::shortest_path_bellman_ford_by(self, from, to, weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted_graph() -> DirectedGraph<i64> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("0", 0);
+        let _ = graph.add_node("1", 1);
+        let _ = graph.add_node("2", 5);
+        let _ = graph.add_node("3", 1);
+        let _ = graph.add_node("4", 1);
+        let _ = graph.add_edge("0", "1");
+        let _ = graph.add_edge("1", "3");
+        let _ = graph.add_edge("0", "2");
+        let _ = graph.add_edge("2", "3");
+        let _ = graph.add_edge("3", "4");
+        graph
+    }
+
+    #[test]
+    fn test_shortest_path_by_dijkstra() {
+        let graph = weighted_graph();
+
+        let path = graph
+            .shortest_path_by("0", "4", |_, to| **to.data())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(path.path(), ["0", "1", "3", "4"]);
+        assert_eq!(*path.cost(), 3);
+    }
+
+    #[test]
+    fn test_shortest_path_by_no_path() {
+        let graph = weighted_graph();
+
+        let path = graph
+            .shortest_path_by("4", "0", |_, to| **to.data())
+            .unwrap();
+
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn test_shortest_path_by_same_node() {
+        let graph = weighted_graph();
+
+        let path = graph
+            .shortest_path_by("2", "2", |_, to| **to.data())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(path.path(), ["2"]);
+        assert_eq!(*path.cost(), 0);
+    }
+
+    #[test]
+    fn test_bellman_ford_negative_weights() {
+        let graph = weighted_graph();
+
+        let path = graph
+            .shortest_path_bellman_ford_by("0", "4", |_, to| -**to.data())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(path.path(), ["0", "2", "3", "4"]);
+        assert_eq!(*path.cost(), -7);
+    }
+
+    #[test]
+    fn test_bellman_ford_negative_cycle() {
+        let mut graph = weighted_graph();
+        let _ = graph.add_edge("4", "1");
+
+        let err = graph
+            .shortest_path_bellman_ford_by("0", "4", |_, to| -**to.data())
+            .unwrap_err();
+
+        match err {
+            OrbweaverError::NegativeCycle(mut cycle) => {
+                cycle.sort_unstable();
+                assert_eq!(cycle, vec!["1", "3", "4"]);
+            }
+            _ => panic!("Expected a negative cycle"),
+        }
+    }
+}
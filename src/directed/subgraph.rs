@@ -0,0 +1,220 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Nodes to keep in [`DirectedGraph::subgraph`]
+    pub(crate) fn subgraph_nodes(
+        &self,
+        nodes: &[impl AsRef<str>],
+    ) -> GraphInteractionResult<HashSet<NodeId>> {
+        nodes.iter().map(|node| self.get_node_id(node)).collect()
+    }
+
+    /// Nodes to keep in [`DirectedGraph::ego_graph`]
+    pub(crate) fn ego_nodes(
+        &self,
+        node: impl AsRef<str>,
+        radius: usize,
+        direction: Direction,
+    ) -> GraphInteractionResult<HashSet<NodeId>> {
+        Ok(crate::algo::traverse(self, &[node], direction)?
+            .max_depth(radius)
+            .include_start(true)
+            .collect())
+    }
+
+    /// Nodes to keep in [`DirectedGraph::subgraph_between`]
+    pub(crate) fn nodes_between(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<HashSet<NodeId>> {
+        let ancestors = self
+            .ancestors(to)?
+            .include_start(true)
+            .collect::<HashSet<_>>();
+        Ok(self
+            .descendants(from)?
+            .include_start(true)
+            .filter(|node_id| ancestors.contains(node_id))
+            .collect())
+    }
+
+    /// Copies `nodes` and every edge between them
+    pub(crate) fn induced_subgraph(&self, nodes: &HashSet<NodeId>) -> Self
+    where
+        Data: Clone,
+        EdgeData: Clone,
+    {
+        // Kept nodes keep their relative order
+        let mut kept = nodes
+            .iter()
+            .map(|node_id| self.index_of(node_id).expect("Node must exist"))
+            .collect::<Vec<_>>();
+        kept.sort_unstable();
+        let mut new_index = vec![u32::MAX; self.n_nodes()];
+        let mut subgraph = DirectedGraph::new();
+        for &node in &kept {
+            new_index[node as usize] =
+                subgraph.insert_node(self.id_at(node).clone(), self.data_at(node).clone());
+        }
+        for &from in &kept {
+            let row = self
+                .children_at(from)
+                .iter()
+                .zip(&self.edge_data[from as usize]);
+            for (&to, data) in row {
+                if new_index[to as usize] != u32::MAX {
+                    subgraph.insert_edge(
+                        new_index[from as usize],
+                        new_index[to as usize],
+                        data.clone(),
+                    );
+                }
+            }
+        }
+        subgraph
+    }
+
+    /// Copies `nodes` and every edge between them into a new graph
+    pub fn subgraph(&self, nodes: &[impl AsRef<str>]) -> GraphInteractionResult<Self>
+    where
+        Data: Clone,
+        EdgeData: Clone,
+    {
+        Ok(self.induced_subgraph(&self.subgraph_nodes(nodes)?))
+    }
+
+    /// Subgraph of the nodes at most `radius` edges away from `node`,
+    /// following edges in `direction`
+    pub fn ego_graph(
+        &self,
+        node: impl AsRef<str>,
+        radius: usize,
+        direction: Direction,
+    ) -> GraphInteractionResult<Self>
+    where
+        Data: Clone,
+        EdgeData: Clone,
+    {
+        Ok(self.induced_subgraph(&self.ego_nodes(node, radius, direction)?))
+    }
+
+    /// Subgraph of every node lying on some path from `from` to `to`.
+    /// Empty if there is no such path.
+    pub fn subgraph_between(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<Self>
+    where
+        Data: Clone,
+        EdgeData: Clone,
+    {
+        Ok(self.induced_subgraph(&self.nodes_between(from, to)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> DirectedGraph<u32, &'static str> {
+        let mut graph = DirectedGraph::new();
+        for (node, data) in [("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)] {
+            let _ = graph.add_node(node, data);
+        }
+        let _ = graph.add_edge_with("a", "b", "ab");
+        let _ = graph.add_edge_with("b", "c", "bc");
+        let _ = graph.add_edge_with("c", "a", "ca");
+        let _ = graph.add_edge_with("c", "d", "cd");
+        let _ = graph.add_edge_with("e", "d", "ed");
+        graph
+    }
+
+    fn sorted_edges<Data>(graph: &DirectedGraph<Data, &str>) -> Vec<(String, String, String)> {
+        let mut edges = graph
+            .edges()
+            .map(|edge| {
+                (
+                    edge.from().into(),
+                    edge.to().into(),
+                    edge.data().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges
+    }
+
+    fn edge(from: &str, to: &str, data: &str) -> (String, String, String) {
+        (from.into(), to.into(), data.into())
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let graph = graph();
+
+        let subgraph = graph.subgraph(&["a", "c", "d"]).unwrap();
+
+        assert_eq!(subgraph.n_nodes(), 3);
+        assert_eq!(*subgraph.get_node("c").unwrap().data(), &3);
+        assert_eq!(
+            sorted_edges(&subgraph),
+            vec![edge("c", "a", "ca"), edge("c", "d", "cd")]
+        );
+        assert!(subgraph.edge_exists("c", "d"));
+        assert!(subgraph.children("a").unwrap().is_empty());
+        assert!(graph.subgraph(&["a", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_ego_graph() {
+        let graph = graph();
+
+        let out = graph.ego_graph("b", 1, Direction::Outgoing).unwrap();
+        assert_eq!(sorted_edges(&out), vec![edge("b", "c", "bc")]);
+
+        let incoming = graph.ego_graph("d", 2, Direction::Incoming).unwrap();
+        let mut nodes = incoming.node_ids().collect::<Vec<_>>();
+        nodes.sort_unstable();
+        assert_eq!(nodes, vec!["b", "c", "d", "e"]);
+        assert_eq!(
+            sorted_edges(&incoming),
+            vec![
+                edge("b", "c", "bc"),
+                edge("c", "d", "cd"),
+                edge("e", "d", "ed")
+            ]
+        );
+
+        assert_eq!(
+            graph
+                .ego_graph("a", 0, Direction::Outgoing)
+                .unwrap()
+                .n_nodes(),
+            1
+        );
+        assert!(graph.ego_graph("x", 1, Direction::Outgoing).is_err());
+    }
+
+    #[test]
+    fn test_subgraph_between() {
+        let graph = graph();
+
+        let between = graph.subgraph_between("b", "d").unwrap();
+        assert_eq!(
+            sorted_edges(&between),
+            vec![
+                edge("a", "b", "ab"),
+                edge("b", "c", "bc"),
+                edge("c", "a", "ca"),
+                edge("c", "d", "cd")
+            ]
+        );
+
+        assert_eq!(graph.subgraph_between("d", "a").unwrap().n_nodes(), 0);
+        assert_eq!(graph.subgraph_between("e", "e").unwrap().n_nodes(), 1);
+        assert!(graph.subgraph_between("a", "x").is_err());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::prelude::*;
+use crate::visit::{Direction, IntoNeighbors, VisitMap, Visitable};
+use std::collections::VecDeque;
+
+/// Lazy breadth-first iterator over the descendants or ancestors of
+/// one or many nodes. Created by [`DirectedGraph::descendants`],
+/// [`DirectedGraph::ancestors`], their `_of` variants and
+/// [`crate::algo::traverse`] for any other graph.
+///
+/// Every node is yielded once, at the smallest depth it can be
+/// reached from any of the start nodes.
+pub struct Traversal<'a, G: Visitable> {
+    graph: &'a G,
+    direction: Direction,
+    queue: VecDeque<(NodeId, usize)>,
+    visited: G::Map<'a>,
+    max_depth: Option<usize>,
+    include_start: bool,
+}
+
+impl<'a, G: IntoNeighbors + Visitable> Traversal<'a, G> {
+    pub(crate) fn new(graph: &'a G, direction: Direction, start: Vec<NodeId>) -> Self {
+        let mut visited = graph.visit_map();
+        for node_id in &start {
+            visited.visit(node_id);
+        }
+        let queue = start.into_iter().map(|node_id| (node_id, 0)).collect();
+        Traversal {
+            graph,
+            direction,
+            queue,
+            visited,
+            max_depth: None,
+            include_start: false,
+        }
+    }
+
+    /// Stops the traversal after `max_depth` edges
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Whether the start nodes are yielded at depth `0`. Defaults to
+    /// `false`.
+    pub fn include_start(mut self, include_start: bool) -> Self {
+        self.include_start = include_start;
+        self
+    }
+
+    /// Yields every node together with its depth
+    pub fn with_depth(self) -> TraversalWithDepth<'a, G> {
+        TraversalWithDepth(self)
+    }
+
+    fn next_with_depth(&mut self) -> Option<(NodeId, usize)> {
+        while let Some((node_id, depth)) = self.queue.pop_front() {
+            if depth < self.max_depth.unwrap_or(usize::MAX) {
+                for neighbor in self.graph.neighbors_directed(&node_id, self.direction) {
+                    if self.visited.visit(neighbor) {
+                        self.queue.push_back((neighbor.clone(), depth + 1));
+                    }
+                }
+            }
+            if depth > 0 || self.include_start {
+                return Some((node_id, depth));
+            }
+        }
+        None
+    }
+}
+
+impl<G: IntoNeighbors + Visitable> Iterator for Traversal<'_, G> {
+    type Item = NodeId;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_depth().map(|(node_id, _)| node_id)
+    }
+}
+
+/// Same as [`Traversal`] but yields the depth of every node
+pub struct TraversalWithDepth<'a, G: Visitable>(Traversal<'a, G>);
+
+impl<G: IntoNeighbors + Visitable> Iterator for TraversalWithDepth<'_, G> {
+    type Item = (NodeId, usize);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_with_depth()
+    }
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Iterates over every node reachable from `node`
+    pub fn descendants(
+        &self,
+        node: impl AsRef<str>,
+    ) -> GraphInteractionResult<Traversal<'_, Self>> {
+        crate::algo::traverse(self, &[node], Direction::Outgoing)
+    }
+
+    /// Iterates over every node reachable from any of `nodes`
+    pub fn descendants_of(
+        &self,
+        nodes: &[impl AsRef<str>],
+    ) -> GraphInteractionResult<Traversal<'_, Self>> {
+        crate::algo::traverse(self, nodes, Direction::Outgoing)
+    }
+
+    /// Iterates over every node that can reach `node`
+    pub fn ancestors(&self, node: impl AsRef<str>) -> GraphInteractionResult<Traversal<'_, Self>> {
+        crate::algo::traverse(self, &[node], Direction::Incoming)
+    }
+
+    /// Iterates over every node that can reach any of `nodes`
+    pub fn ancestors_of(
+        &self,
+        nodes: &[impl AsRef<str>],
+    ) -> GraphInteractionResult<Traversal<'_, Self>> {
+        crate::algo::traverse(self, nodes, Direction::Incoming)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> DirectedGraph<()> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_node("3", ());
+        let _ = graph.add_node("4", ());
+        let _ = graph.add_node("5", ());
+        let _ = graph.add_path(&["0", "1", "3", "5"]);
+        let _ = graph.add_path(&["0", "2", "4"]);
+        let _ = graph.add_edge("2", "3");
+        graph
+    }
+
+    fn sorted(nodes: impl Iterator<Item = NodeId>) -> Vec<NodeId> {
+        let mut nodes = nodes.collect::<Vec<_>>();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    #[test]
+    fn test_descendants() {
+        let graph = tree();
+
+        assert_eq!(sorted(graph.descendants("1").unwrap()), vec!["3", "5"]);
+        assert_eq!(
+            sorted(graph.descendants("1").unwrap().include_start(true)),
+            vec!["1", "3", "5"]
+        );
+        assert_eq!(
+            sorted(graph.descendants("5").unwrap()),
+            Vec::<NodeId>::new()
+        );
+        assert!(graph.descendants("6").is_err());
+    }
+
+    #[test]
+    fn test_descendants_max_depth() {
+        let graph = tree();
+
+        assert_eq!(
+            sorted(graph.descendants("0").unwrap().max_depth(1)),
+            vec!["1", "2"]
+        );
+        assert_eq!(
+            sorted(
+                graph
+                    .descendants("0")
+                    .unwrap()
+                    .max_depth(0)
+                    .include_start(true)
+            ),
+            vec!["0"]
+        );
+    }
+
+    #[test]
+    fn test_descendants_with_depth() {
+        let graph = tree();
+
+        let mut depths = graph
+            .descendants("0")
+            .unwrap()
+            .include_start(true)
+            .with_depth()
+            .collect::<Vec<_>>();
+        depths.sort_unstable();
+
+        assert_eq!(
+            depths,
+            vec![
+                ("0".into(), 0),
+                ("1".into(), 1),
+                ("2".into(), 1),
+                ("3".into(), 2),
+                ("4".into(), 2),
+                ("5".into(), 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let graph = tree();
+
+        assert_eq!(sorted(graph.ancestors("3").unwrap()), vec!["0", "1", "2"]);
+        assert_eq!(
+            sorted(graph.ancestors_of(&["4", "5"]).unwrap().max_depth(1)),
+            vec!["2", "3"]
+        );
+        assert_eq!(
+            sorted(graph.ancestors_of(&["4", "5"]).unwrap().include_start(true)),
+            vec!["0", "1", "2", "3", "4", "5"]
+        );
+    }
+
+    #[test]
+    fn test_traversal_is_lazy() {
+        let graph = tree();
+
+        let first = graph.descendants("0").unwrap().next().unwrap();
+
+        assert!(first == "1" || first == "2");
+    }
+}
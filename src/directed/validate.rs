@@ -0,0 +1,197 @@
+use crate::prelude::*;
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Checks that the rows, the interner and the edge count agree with
+    /// each other, returning the first inconsistency found. It walks
+    /// the whole graph so it only exists with debug assertions, where
+    /// every mutation also checks the nodes and edges it touched.
+    #[cfg(debug_assertions)]
+    pub fn validate(&self) -> GraphInteractionResult<()> {
+        self.validate_rows()?;
+        let mut n_edges = 0;
+        for node in 0..self.n_nodes() as u32 {
+            self.validate_node(node)?;
+            n_edges += self.children_at(node).len();
+        }
+        if n_edges != self.n_edges {
+            return Err(InvalidGraph::EdgeCount {
+                stored: self.n_edges,
+                counted: n_edges,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Checks every per node field has one row per node
+    pub(crate) fn validate_rows(&self) -> GraphInteractionResult<()> {
+        let nodes = self.ids.len();
+        for rows in [
+            self.data.len(),
+            self.children.len(),
+            self.edge_data.len(),
+            self.parents.len(),
+        ] {
+            if rows != nodes {
+                return Err(InvalidGraph::RowCount { rows, nodes }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the interned index of the node and every edge leaving or
+    /// reaching it
+    pub(crate) fn validate_node(&self, node: u32) -> GraphInteractionResult<()> {
+        let node_id = self.id_at(node);
+        if self.index_of(node_id) != Some(node) {
+            return Err(InvalidGraph::StaleIndex(node_id.clone()).into());
+        }
+        for &child in self.children_at(node) {
+            self.stored_edge(node, child)?;
+        }
+        for &parent in self.parents_at(node) {
+            self.stored_edge(parent, node)?;
+        }
+        self.validate_edge_data(node)
+    }
+
+    /// Checks the children of the node and the data of its edges line up
+    pub(crate) fn validate_edge_data(&self, node: u32) -> GraphInteractionResult<()> {
+        let children = self.children_at(node);
+        let n_data = self.edge_data[node as usize].len();
+        if n_data > children.len() {
+            return Err(InvalidGraph::StaleEdgeData(self.id_at(node).clone()).into());
+        }
+        match children.get(n_data) {
+            Some(&child) => Err(InvalidGraph::MissingEdgeData(
+                self.id_at(node).clone(),
+                self.id_at(child).clone(),
+            )
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the edge is stored, failing unless it is stored exactly
+    /// once in the children of `from` and in the parents of `to` or in
+    /// neither
+    pub(crate) fn stored_edge(&self, from: u32, to: u32) -> GraphInteractionResult<bool> {
+        for (node, neighbor) in [(from, to), (to, from)] {
+            if neighbor as usize >= self.n_nodes() {
+                return Err(InvalidGraph::DanglingEdge(self.id_at(node).clone(), neighbor).into());
+            }
+        }
+        let stored = [
+            self.children_at(from)
+                .iter()
+                .filter(|&&child| child == to)
+                .count(),
+            self.parents_at(to)
+                .iter()
+                .filter(|&&parent| parent == from)
+                .count(),
+        ];
+        let edge = || (self.id_at(from).clone(), self.id_at(to).clone());
+        match stored {
+            [0, 0] => Ok(false),
+            [1, 1] => Ok(true),
+            [0, _] | [_, 0] => Err(InvalidGraph::AsymmetricEdge(edge().0, edge().1).into()),
+            _ => Err(InvalidGraph::RepeatedEdge(edge().0, edge().1).into()),
+        }
+    }
+}
+
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use super::*;
+    use crate::testing::{check_against_model, mutations};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_validate_detects_inconsistencies() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("a", ());
+        let _ = graph.add_node("b", ());
+        let _ = graph.add_edge("a", "b");
+        assert!(graph.validate().is_ok());
+
+        let mut broken = graph.clone();
+        broken.n_edges = 2;
+        assert!(matches!(
+            broken.validate(),
+            Err(OrbweaverError::Invalid(InvalidGraph::EdgeCount {
+                stored: 2,
+                counted: 1
+            }))
+        ));
+
+        let mut broken = graph.clone();
+        broken.parents[1].clear();
+        assert!(matches!(
+            broken.validate(),
+            Err(OrbweaverError::Invalid(InvalidGraph::AsymmetricEdge(from, to))) if from == "a" && to == "b"
+        ));
+
+        let mut broken = graph.clone();
+        broken.edge_data[0].clear();
+        assert!(matches!(
+            broken.validate(),
+            Err(OrbweaverError::Invalid(InvalidGraph::MissingEdgeData(from, to))) if from == "a" && to == "b"
+        ));
+
+        let mut broken = graph.clone();
+        broken.children[0].push(1);
+        broken.edge_data[0].push(());
+        assert!(matches!(
+            broken.validate(),
+            Err(OrbweaverError::Invalid(InvalidGraph::RepeatedEdge(from, to))) if from == "a" && to == "b"
+        ));
+
+        let mut broken = graph.clone();
+        broken.data.pop();
+        assert!(matches!(
+            broken.validate(),
+            Err(OrbweaverError::Invalid(InvalidGraph::RowCount {
+                rows: 1,
+                nodes: 2
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_edges_and_node_removal_keep_n_edges() {
+        let mut graph = DirectedGraph::<(), u32>::new();
+        let _ = graph.add_node("a", ());
+        let _ = graph.add_node("b", ());
+        let _ = graph.add_edge_with("a", "b", 1);
+        let _ = graph.add_edge_with("a", "b", 2);
+        assert_eq!(graph.n_edges(), 1);
+        assert_eq!(**graph.get_edge("a", "b").unwrap().data(), 2);
+
+        let _ = graph.add_edge_with_policy("a", "b", 3, OnDuplicateEdge::Ignore);
+        assert_eq!(**graph.get_edge("a", "b").unwrap().data(), 2);
+        assert!(matches!(
+            graph.add_edge_with_policy("a", "b", 3, OnDuplicateEdge::Error),
+            Err(OrbweaverError::DuplicateEdge(from, to)) if from == "a" && to == "b"
+        ));
+        let _ = graph.add_edge_with_policy("a", "b", 4, OnDuplicateEdge::Replace);
+        assert_eq!(**graph.get_edge("a", "b").unwrap().data(), 4);
+        assert!(graph
+            .add_edge_with_policy("b", "a", 5, OnDuplicateEdge::Error)
+            .is_ok());
+
+        let _ = graph.add_edge("b", "b");
+        assert_eq!(graph.n_edges(), 3);
+        graph.remove_node("b");
+        assert_eq!(graph.n_edges(), 0);
+        assert!(graph.validate().is_ok());
+    }
+
+    proptest! {
+        #[test]
+        fn test_random_mutations_keep_graph_consistent(ops in mutations()) {
+            check_against_model(DirectedGraph::new(), false, &ops)?;
+        }
+    }
+}
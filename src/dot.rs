@@ -0,0 +1,761 @@
+//! Graphviz DOT export and import.
+//!
+//! Graphs are written with [`DirectedGraph::to_dot`] or
+//! [`DirectedGraph::to_dot_with`] and read with [`from_dot`], which
+//! stores the attributes of every node and edge as strings.
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Attributes of a node or an edge in a DOT file
+pub type DotAttributes = HashMap<String, String>;
+
+/// Graph produced by [`from_dot`]
+pub type DotGraph = DirectedGraph<DotAttributes, DotAttributes>;
+
+/// Attribute set on nodes declared inside a named subgraph, holding
+/// the name of the innermost subgraph, e.g. `cluster_0`
+pub const SUBGRAPH_ATTRIBUTE: &str = "subgraph";
+
+#[derive(Debug)]
+pub struct DotParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl DotParseError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        DotParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DotParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unable to parse DOT, line {}: {}",
+            self.line, self.message
+        )
+    }
+}
+
+impl std::error::Error for DotParseError {}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn write_attributes(out: &mut String, mut attributes: Vec<(String, String)>) {
+    if attributes.is_empty() {
+        return;
+    }
+    attributes.sort_unstable();
+    let attributes = attributes
+        .iter()
+        .map(|(key, value)| format!("{}={}", quote(key), quote(value)))
+        .collect::<Vec<_>>();
+    let _ = write!(out, " [{}]", attributes.join(", "));
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Writes the graph as a DOT digraph without any attributes
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(|_| Vec::new(), |_| Vec::new())
+    }
+
+    /// Writes the graph as a DOT digraph. The attributes of every node
+    /// and edge are computed by `node_attributes` and
+    /// `edge_attributes`. Nodes and edges are sorted so the output is
+    /// stable.
+    pub fn to_dot_with<N, E>(&self, mut node_attributes: N, mut edge_attributes: E) -> String
+    where
+        N: FnMut(Node<&Data>) -> Vec<(String, String)>,
+        E: FnMut(Edge<&EdgeData>) -> Vec<(String, String)>,
+    {
+        let mut out = String::from("digraph {\n");
+
+        let mut nodes = self.nodes().collect::<Vec<_>>();
+        nodes.sort_unstable_by(|a, b| a.node_id.cmp(&b.node_id));
+        for node in nodes {
+            let _ = write!(out, "    {}", quote(&node.node_id));
+            write_attributes(&mut out, node_attributes(node));
+            out.push_str(";\n");
+        }
+
+        let mut edges = self.edges().collect::<Vec<_>>();
+        edges.sort_unstable_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+        for edge in edges {
+            let _ = write!(out, "    {} -> {}", quote(&edge.from), quote(&edge.to));
+            write_attributes(&mut out, edge_attributes(edge));
+            out.push_str(";\n");
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Identifier and whether it was quoted, quoted identifiers are
+    /// never keywords
+    Id(String, bool),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Semicolon,
+    Comma,
+    Equal,
+    Colon,
+    Arrow,
+    UndirectedEdge,
+    Plus,
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, DotParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    let mut at_line_start = true;
+
+    while let Some(c) = chars.next() {
+        let token_line = line;
+        match c {
+            '\n' => {
+                line += 1;
+                at_line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // Preprocessor output lines
+            '#' if at_line_start => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+                continue;
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+                at_line_start = true;
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            previous = c;
+                        }
+                        None => return Err(DotParseError::new(token_line, "Unterminated comment")),
+                    }
+                }
+                continue;
+            }
+            '{' => tokens.push((Token::LBrace, line)),
+            '}' => tokens.push((Token::RBrace, line)),
+            '[' => tokens.push((Token::LBracket, line)),
+            ']' => tokens.push((Token::RBracket, line)),
+            ';' => tokens.push((Token::Semicolon, line)),
+            ',' => tokens.push((Token::Comma, line)),
+            '=' => tokens.push((Token::Equal, line)),
+            ':' => tokens.push((Token::Colon, line)),
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                tokens.push((Token::Arrow, line));
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                tokens.push((Token::UndirectedEdge, line));
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('"') => value.push('"'),
+                            Some('\\') => value.push('\\'),
+                            Some('n') => value.push('\n'),
+                            // Line continuation
+                            Some('\n') => line += 1,
+                            Some(c) => {
+                                value.push('\\');
+                                value.push(c);
+                            }
+                            None => break,
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                        None => return Err(DotParseError::new(token_line, "Unterminated string")),
+                    }
+                }
+                tokens.push((Token::Id(value, true), token_line));
+            }
+            '<' => {
+                let mut value = String::new();
+                let mut depth = 1;
+                loop {
+                    match chars.next() {
+                        Some('<') => {
+                            depth += 1;
+                            value.push('<');
+                        }
+                        Some('>') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                            value.push('>');
+                        }
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                        None => {
+                            return Err(DotParseError::new(token_line, "Unterminated HTML string"))
+                        }
+                    }
+                }
+                tokens.push((Token::Id(value, true), token_line));
+            }
+            '+' => tokens.push((Token::Plus, line)),
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || !c.is_ascii() => {
+                let mut value = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || c == '.' || !c.is_ascii() {
+                        value.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push((Token::Id(value, false), line));
+            }
+            c => {
+                return Err(DotParseError::new(
+                    line,
+                    format!("Unexpected character `{c}`"),
+                ))
+            }
+        }
+        at_line_start = false;
+    }
+
+    // Join string concatenations `"a" + "b"`
+    let mut joined: Vec<(Token, usize)> = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter();
+    while let Some((token, line)) = tokens.next() {
+        if token == Token::Plus {
+            match (joined.last_mut(), tokens.next()) {
+                (Some((Token::Id(value, true), _)), Some((Token::Id(next, true), _))) => {
+                    value.push_str(&next);
+                }
+                _ => return Err(DotParseError::new(line, "Expected a string after `+`")),
+            }
+        } else {
+            joined.push((token, line));
+        }
+    }
+
+    Ok(joined)
+}
+
+/// How deeply subgraphs may nest before the parser gives up, so hostile
+/// input cannot overflow the stack
+const MAX_DEPTH: usize = 128;
+
+#[derive(Clone, Default)]
+struct Scope {
+    node_attributes: DotAttributes,
+    edge_attributes: DotAttributes,
+    subgraph: Option<String>,
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    depth: usize,
+    graph: DotGraph,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    /// Line of the last consumed token
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position.saturating_sub(1))
+            .or(self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> DotParseError {
+        DotParseError::new(self.line(), message)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), DotParseError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(self.error(format!("Expected {expected:?}, found {token:?}"))),
+            None => Err(self.error(format!("Expected {expected:?}, found end of input"))),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(value, false)) if value.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_id(&mut self) -> Result<String, DotParseError> {
+        match self.next() {
+            Some(Token::Id(value, _)) => Ok(value),
+            Some(token) => Err(self.error(format!("Expected an identifier, found {token:?}"))),
+            None => Err(self.error("Expected an identifier, found end of input")),
+        }
+    }
+
+    fn parse_graph(&mut self) -> Result<(), DotParseError> {
+        if self.is_keyword("strict") {
+            self.next();
+        }
+        if self.is_keyword("graph") {
+            return Err(self.error("Only directed graphs (`digraph`) are supported"));
+        }
+        if !self.is_keyword("digraph") {
+            return Err(self.error("Expected `digraph`"));
+        }
+        self.next();
+        if let Some(Token::Id(..)) = self.peek() {
+            self.next();
+        }
+        self.expect(Token::LBrace)?;
+        self.parse_statements(&mut Scope::default())?;
+        self.expect(Token::RBrace)?;
+        if let Some(token) = self.peek() {
+            return Err(self.error(format!("Unexpected {token:?} after the graph")));
+        }
+        Ok(())
+    }
+
+    /// Parses statements until the closing brace, returning every node
+    /// mentioned so subgraphs can be used as edge endpoints
+    fn parse_statements(&mut self, scope: &mut Scope) -> Result<Vec<NodeId>, DotParseError> {
+        let mut mentioned = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Token::RBrace) => return Ok(mentioned),
+                Some(Token::Semicolon) => {
+                    self.next();
+                }
+                _ => self.parse_statement(scope, &mut mentioned)?,
+            }
+        }
+    }
+
+    fn parse_statement(
+        &mut self,
+        scope: &mut Scope,
+        mentioned: &mut Vec<NodeId>,
+    ) -> Result<(), DotParseError> {
+        for (keyword, is_node) in [("node", Some(true)), ("edge", Some(false)), ("graph", None)] {
+            if self.is_keyword(keyword) {
+                self.next();
+                let attributes = self.parse_attribute_lists()?;
+                match is_node {
+                    Some(true) => scope.node_attributes.extend(attributes),
+                    Some(false) => scope.edge_attributes.extend(attributes),
+                    None => (),
+                }
+                return Ok(());
+            }
+        }
+
+        let first = self.parse_operand(scope)?;
+
+        if self.peek() == Some(&Token::Equal) {
+            // Graph attribute statement `ID = ID`, not stored
+            self.next();
+            self.parse_id()?;
+            return Ok(());
+        }
+
+        let mut operands = vec![first];
+        loop {
+            match self.peek() {
+                Some(Token::Arrow) => {
+                    self.next();
+                    operands.push(self.parse_operand(scope)?);
+                }
+                Some(Token::UndirectedEdge) => {
+                    return Err(self.error("Undirected edges (`--`) are not supported"));
+                }
+                _ => break,
+            }
+        }
+
+        let attributes = if self.peek() == Some(&Token::LBracket) {
+            self.parse_attribute_lists()?
+        } else {
+            DotAttributes::new()
+        };
+
+        for operand in &operands {
+            mentioned.extend(operand.iter().cloned());
+        }
+
+        if operands.len() == 1 {
+            for node_id in &operands[0] {
+                let data = self
+                    .graph
+                    .node_data_mut(node_id)
+                    .expect("Operands are always added");
+                data.extend(attributes.clone());
+            }
+            return Ok(());
+        }
+
+        let mut edge_attributes = scope.edge_attributes.clone();
+        edge_attributes.extend(attributes);
+        for pair in operands.windows(2) {
+            for from in &pair[0] {
+                for to in &pair[1] {
+                    if self.graph.edge_exists(from, to) {
+                        let data = self.graph.edge_data_mut(from, to).expect("Edge exists");
+                        data.extend(edge_attributes.clone());
+                    } else {
+                        self.graph
+                            .add_edge_with(from, to, edge_attributes.clone())
+                            .expect("Operands are always added");
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A node id or a subgraph, returns the nodes it contains
+    fn parse_operand(&mut self, scope: &Scope) -> Result<Vec<NodeId>, DotParseError> {
+        if self.is_keyword("subgraph") || self.peek() == Some(&Token::LBrace) {
+            let mut inner = scope.clone();
+            if self.is_keyword("subgraph") {
+                self.next();
+                if let Some(Token::Id(..)) = self.peek() {
+                    inner.subgraph = Some(self.parse_id()?);
+                }
+            }
+            self.expect(Token::LBrace)?;
+            if self.depth == MAX_DEPTH {
+                return Err(self.error(format!("Subgraphs nested deeper than {MAX_DEPTH} levels")));
+            }
+            self.depth += 1;
+            let mut nodes = self.parse_statements(&mut inner)?;
+            self.depth -= 1;
+            self.expect(Token::RBrace)?;
+            nodes.sort_unstable();
+            nodes.dedup();
+            return Ok(nodes);
+        }
+
+        let id = self.parse_id()?;
+        // Ports and compass points are ignored
+        while self.peek() == Some(&Token::Colon) {
+            self.next();
+            self.parse_id()?;
+        }
+
+        if self.peek() == Some(&Token::Equal) {
+            // This is a graph attribute, let the statement handle it
+            return Ok(Vec::new());
+        }
+
+        if self.graph.get_node(&id).is_err() {
+            self.graph
+                .add_node(&id, scope.node_attributes.clone())
+                .expect("Node does not exist yet");
+        }
+        let data = self.graph.node_data_mut(&id).expect("Node was added");
+        if let Some(subgraph) = &scope.subgraph {
+            data.entry(SUBGRAPH_ATTRIBUTE.to_string())
+                .or_insert_with(|| subgraph.clone());
+        }
+
+        Ok(vec![self.graph.get_node_id(&id).expect("Node was added")])
+    }
+
+    fn parse_attribute_lists(&mut self) -> Result<DotAttributes, DotParseError> {
+        let mut attributes = DotAttributes::new();
+        while self.peek() == Some(&Token::LBracket) {
+            self.next();
+            loop {
+                match self.peek() {
+                    Some(Token::RBracket) => {
+                        self.next();
+                        break;
+                    }
+                    Some(Token::Comma) | Some(Token::Semicolon) => {
+                        self.next();
+                    }
+                    _ => {
+                        let key = self.parse_id()?;
+                        self.expect(Token::Equal)?;
+                        let value = self.parse_id()?;
+                        attributes.insert(key, value);
+                    }
+                }
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// Reads a DOT digraph. The attributes of every node and edge are kept
+/// as strings, including the defaults set with `node [...]` and
+/// `edge [...]`. Nodes declared inside a named subgraph or cluster get
+/// the [`SUBGRAPH_ATTRIBUTE`] attribute. Ports are ignored.
+pub fn from_dot(input: &str) -> Result<DotGraph, OrbweaverError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        position: 0,
+        depth: 0,
+        graph: DirectedGraph::new(),
+    };
+    parser.parse_graph()?;
+    Ok(parser.graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(pairs: &[(&str, &str)]) -> DotAttributes {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn to_pairs(attributes: &DotAttributes) -> Vec<(String, String)> {
+        attributes
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    fn sorted_edges<Data, EdgeData>(
+        graph: &DirectedGraph<Data, EdgeData>,
+    ) -> Vec<(NodeId, NodeId)> {
+        let mut edges = graph
+            .edges()
+            .map(|edge| (edge.from(), edge.to()))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = DirectedGraph::<u32, &str>::new();
+        let _ = graph.add_node("b", 2);
+        let _ = graph.add_node("a", 1);
+        let _ = graph.add_node("say \"hi\"", 3);
+        let _ = graph.add_edge_with("a", "b", "x");
+        let _ = graph.add_edge_with("b", "say \"hi\"", "y");
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    \"a\";\n    \"b\";\n    \"say \\\"hi\\\"\";\n    \"a\" -> \"b\";\n    \"b\" -> \"say \\\"hi\\\"\";\n}\n"
+        );
+
+        let dot = graph.to_dot_with(
+            |node| vec![("weight".to_string(), node.data().to_string())],
+            |edge| vec![("label".to_string(), edge.data().to_string())],
+        );
+        assert!(dot.contains("\"a\" [\"weight\"=\"1\"];"));
+        assert!(dot.contains("\"a\" -> \"b\" [\"label\"=\"x\"];"));
+    }
+
+    #[test]
+    fn test_from_dot() {
+        let graph = from_dot(
+            r#"
+            // A comment
+            digraph pipeline {
+                rankdir = LR;
+                node [shape=box];
+                fetch [label="Fetch data"];
+                fetch -> clean -> model [weight=2];
+                /* Nodes can be declared
+                   after being used */
+                model [color=red]
+                report;
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(graph.n_nodes(), 4);
+        assert_eq!(
+            *graph.get_node("fetch").unwrap().data(),
+            &attributes(&[("shape", "box"), ("label", "Fetch data")])
+        );
+        assert_eq!(
+            *graph.get_node("model").unwrap().data(),
+            &attributes(&[("shape", "box"), ("color", "red")])
+        );
+        assert_eq!(
+            *graph.get_edge("clean", "model").unwrap().data(),
+            &attributes(&[("weight", "2")])
+        );
+        assert!(!graph.has_children("report").unwrap());
+    }
+
+    #[test]
+    fn test_from_dot_subgraphs() {
+        let graph = from_dot(
+            r#"
+            digraph {
+                subgraph cluster_ingest {
+                    node [color=blue];
+                    a -> b;
+                }
+                subgraph cluster_output {
+                    c; d;
+                }
+                b -> { c d };
+                { e f } -> a;
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            *graph.get_node("a").unwrap().data(),
+            &attributes(&[("color", "blue"), ("subgraph", "cluster_ingest")])
+        );
+        assert_eq!(
+            *graph.get_node("c").unwrap().data(),
+            &attributes(&[("subgraph", "cluster_output")])
+        );
+        assert!(graph.get_node("e").unwrap().data().is_empty());
+        assert_eq!(
+            sorted_edges(&graph),
+            vec![
+                ("a".into(), "b".into()),
+                ("b".into(), "c".into()),
+                ("b".into(), "d".into()),
+                ("e".into(), "a".into()),
+                ("f".into(), "a".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_dot_errors() {
+        assert!(from_dot("graph { a -- b }").is_err());
+        assert!(from_dot("digraph { a -> }").is_err());
+
+        assert!(matches!(
+            from_dot("digraph {\n a -> b\n c [label=\"open }\n"),
+            Err(OrbweaverError::Dot(DotParseError { line: 3, .. }))
+        ));
+        assert!(matches!(
+            from_dot("digraph {\n a -> b;\n c [label=];\n}"),
+            Err(OrbweaverError::Dot(DotParseError { line: 3, .. }))
+        ));
+    }
+
+    #[test]
+    fn test_from_dot_depth_limit() {
+        let nested = |depth| {
+            format!(
+                "digraph {{ {} a {} }}",
+                "{".repeat(depth),
+                "}".repeat(depth)
+            )
+        };
+        assert!(from_dot(&nested(MAX_DEPTH)).is_ok());
+        assert!(from_dot(&nested(MAX_DEPTH + 1)).is_err());
+        // Deep enough to overflow the stack without the limit
+        assert!(from_dot(&nested(1_000_000)).is_err());
+        assert!(from_dot(&format!(
+            "digraph {{ {}",
+            "a -> subgraph s {".repeat(1_000_000)
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_dot_round_trip() {
+        let mut graph = DotGraph::new();
+        let _ = graph.add_node("a", attributes(&[("label", "A \"quoted\" label")]));
+        let _ = graph.add_node("b c", attributes(&[("shape", "box"), ("color", "red")]));
+        let _ = graph.add_node("d", attributes(&[("label", "back\\slash\nnewline")]));
+        let _ = graph.add_edge_with("a", "b c", attributes(&[("weight", "1.5")]));
+        let _ = graph.add_edge_with("b c", "d", DotAttributes::new());
+
+        let dot = graph.to_dot_with(|node| to_pairs(node.data()), |edge| to_pairs(edge.data()));
+        let parsed = from_dot(&dot).unwrap();
+
+        assert_eq!(parsed.n_nodes(), graph.n_nodes());
+        for node in graph.nodes() {
+            assert_eq!(parsed.get_node(node.id()).unwrap().data(), node.data());
+        }
+        assert_eq!(sorted_edges(&parsed), sorted_edges(&graph));
+        for edge in graph.edges() {
+            assert_eq!(
+                parsed.get_edge(edge.from(), edge.to()).unwrap().data(),
+                edge.data()
+            );
+        }
+        assert_eq!(
+            parsed.to_dot_with(|node| to_pairs(node.data()), |edge| to_pairs(edge.data())),
+            dot
+        );
+    }
+}
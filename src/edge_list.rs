@@ -0,0 +1,487 @@
+//! Edge list import and export, usually two column parent/child CSV
+//! tables.
+//!
+//! Fields follow RFC 4180: they may be quoted, quotes inside a quoted
+//! field are doubled and quoted fields may span several lines.
+use crate::prelude::*;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+/// Format of an edge list or node table
+#[derive(Debug, Clone)]
+pub struct EdgeListOptions {
+    delimiter: char,
+    quote: char,
+    header: bool,
+    source_column: usize,
+    target_column: usize,
+}
+
+impl Default for EdgeListOptions {
+    fn default() -> Self {
+        EdgeListOptions {
+            delimiter: ',',
+            quote: '"',
+            header: true,
+            source_column: 0,
+            target_column: 1,
+        }
+    }
+}
+
+impl EdgeListOptions {
+    /// Field delimiter. Defaults to `,`.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Quote character. Defaults to `"`.
+    pub fn quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Whether the first record is a header. When reading it is
+    /// skipped, when writing it is included. Defaults to `true`.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Zero based columns holding the parent and the child of every
+    /// edge, any other column is ignored. Defaults to `0` and `1`.
+    pub fn columns(mut self, source: usize, target: usize) -> Self {
+        self.source_column = source;
+        self.target_column = target;
+        self
+    }
+
+    fn write_field(&self, out: &mut impl Write, field: &str) -> std::io::Result<()> {
+        let needs_quotes = field.is_empty()
+            || field.contains([self.delimiter, self.quote, '\n', '\r'])
+            || field.trim() != field;
+        if !needs_quotes {
+            return out.write_all(field.as_bytes());
+        }
+        let mut quoted = String::with_capacity(field.len() + 2);
+        quoted.push(self.quote);
+        for c in field.chars() {
+            if c == self.quote {
+                quoted.push(c);
+            }
+            quoted.push(c);
+        }
+        quoted.push(self.quote);
+        out.write_all(quoted.as_bytes())
+    }
+
+    fn write_record<'a>(
+        &self,
+        out: &mut impl Write,
+        fields: impl IntoIterator<Item = &'a str>,
+    ) -> std::io::Result<()> {
+        let mut delimiter = [0; 4];
+        let delimiter = self.delimiter.encode_utf8(&mut delimiter);
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                out.write_all(delimiter.as_bytes())?;
+            }
+            self.write_field(out, field)?;
+        }
+        out.write_all(b"\n")
+    }
+}
+
+#[derive(Debug)]
+pub enum EdgeListError {
+    Io(std::io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl EdgeListError {
+    fn parse(line: usize, message: impl Into<String>) -> Self {
+        Self::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for EdgeListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Unable to read edge list: {err}"),
+            Self::Parse { line, message } => {
+                write!(f, "Unable to parse edge list, line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EdgeListError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for EdgeListError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Unquoted fields are trimmed, quoted ones are kept as written
+fn finish_field(field: &mut String, quoted: bool) -> String {
+    let field = std::mem::take(field);
+    match quoted {
+        true => field,
+        false => field.trim().to_string(),
+    }
+}
+
+/// Reads the input one record at a time, each with the line it starts
+/// on. Only the current line is kept in memory. Blank lines are skipped.
+struct Records<'a, R> {
+    reader: R,
+    options: &'a EdgeListOptions,
+    buffer: String,
+    /// Byte offset of the next character in `buffer`
+    position: usize,
+    line: usize,
+}
+
+impl<'a, R: BufRead> Records<'a, R> {
+    fn new(reader: R, options: &'a EdgeListOptions) -> Self {
+        Records {
+            reader,
+            options,
+            buffer: String::new(),
+            position: 0,
+            line: 1,
+        }
+    }
+
+    /// Reads the next line once the current one is consumed. Returns
+    /// `false` at the end of the input.
+    fn fill(&mut self) -> Result<bool, EdgeListError> {
+        if self.position == self.buffer.len() {
+            self.buffer.clear();
+            self.position = 0;
+            return Ok(self.reader.read_line(&mut self.buffer)? > 0);
+        }
+        Ok(true)
+    }
+
+    fn next_char(&mut self) -> Result<Option<char>, EdgeListError> {
+        if !self.fill()? {
+            return Ok(None);
+        }
+        let c = self.buffer[self.position..]
+            .chars()
+            .next()
+            .expect("Buffer is not consumed");
+        self.position += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Ok(Some(c))
+    }
+
+    /// Next character on the current line. Lines end with `\n`, so
+    /// anything that must be followed by a character on the same line
+    /// never needs to look further.
+    fn peek_char(&self) -> Option<char> {
+        self.buffer[self.position..].chars().next()
+    }
+
+    fn next_record(&mut self) -> Result<Option<(usize, Vec<String>)>, EdgeListError> {
+        let (quote, delimiter) = (self.options.quote, self.options.delimiter);
+        while self.fill()? {
+            let start_line = self.line;
+            let mut fields = Vec::new();
+            let mut field = String::new();
+            let mut quoted = false;
+            loop {
+                match self.next_char()? {
+                    None => {
+                        fields.push(finish_field(&mut field, quoted));
+                        break;
+                    }
+                    Some(c) if c == quote && field.trim().is_empty() && !quoted => {
+                        // Opening quote, whitespace before it is dropped
+                        field.clear();
+                        quoted = true;
+                        loop {
+                            match self.next_char()? {
+                                None => {
+                                    return Err(EdgeListError::parse(
+                                        start_line,
+                                        "Unterminated quoted field",
+                                    ))
+                                }
+                                Some(c) if c == quote => {
+                                    if self.peek_char() == Some(quote) {
+                                        self.next_char()?;
+                                        field.push(c);
+                                    } else {
+                                        break;
+                                    }
+                                }
+                                Some(c) => field.push(c),
+                            }
+                        }
+                    }
+                    Some(c) if c == delimiter => {
+                        fields.push(finish_field(&mut field, quoted));
+                        quoted = false;
+                    }
+                    Some('\n') => {
+                        fields.push(finish_field(&mut field, quoted));
+                        break;
+                    }
+                    Some('\r') if self.peek_char() == Some('\n') => (),
+                    Some(c) if quoted => {
+                        if !c.is_whitespace() {
+                            return Err(EdgeListError::parse(
+                                self.line,
+                                format!("Unexpected `{c}` after a quoted field"),
+                            ));
+                        }
+                    }
+                    Some(c) => field.push(c),
+                }
+            }
+            let is_blank = fields.len() == 1 && fields[0].is_empty() && !quoted;
+            if !is_blank {
+                return Ok(Some((start_line, fields)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: BufRead> Iterator for Records<'_, R> {
+    type Item = Result<(usize, Vec<String>), EdgeListError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+impl<Data: Default, EdgeData: Default> DirectedGraph<Data, EdgeData> {
+    /// Reads a graph from an edge list one record at a time. Nodes are
+    /// created with the default data the first time they appear,
+    /// repeated edges are ignored. Unquoted fields are trimmed.
+    pub fn from_edge_list(
+        reader: impl Read,
+        options: EdgeListOptions,
+    ) -> Result<Self, OrbweaverError> {
+        let mut graph = DirectedGraph::new();
+        let skip = usize::from(options.header);
+        let records = Records::new(BufReader::new(reader), &options);
+        for (index, record) in records.enumerate() {
+            let (line, fields) = record?;
+            if index < skip {
+                continue;
+            }
+            let field = |column: usize| {
+                let value = fields.get(column).map(String::as_str).ok_or_else(|| {
+                    EdgeListError::parse(
+                        line,
+                        format!(
+                            "Expected at least {} fields, found {}",
+                            column + 1,
+                            fields.len()
+                        ),
+                    )
+                })?;
+                match value.is_empty() {
+                    true => Err(EdgeListError::parse(
+                        line,
+                        format!("Empty node id in column {}", column + 1),
+                    )),
+                    false => Ok(value),
+                }
+            };
+            let from = field(options.source_column)?;
+            let to = field(options.target_column)?;
+            for node in [from, to] {
+                if graph.get_node_id(node).is_err() {
+                    let _ = graph.add_node(node, Data::default());
+                }
+            }
+            if !graph.edge_exists(from, to) {
+                graph.add_edge(from, to).expect("Both nodes exist");
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Writes every edge as a `parent,child` record, sorted by parent
+    /// and then by child. Nodes without edges are not included, use
+    /// [`DirectedGraph::write_node_table`] to keep them.
+    pub fn write_edge_list(
+        &self,
+        writer: impl Write,
+        options: &EdgeListOptions,
+    ) -> std::io::Result<()> {
+        let mut edges = self
+            .edges()
+            .map(|edge| (edge.from(), edge.to()))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+
+        let mut out = BufWriter::new(writer);
+        if options.header {
+            options.write_record(&mut out, ["parent", "child"])?;
+        }
+        for (from, to) in &edges {
+            options.write_record(&mut out, [from.as_ref(), to.as_ref()])?;
+        }
+        out.flush()
+    }
+
+    /// Writes one record per node, sorted by id, with the id followed
+    /// by the values returned by `values`. The header holds `id` and
+    /// the names in `columns`.
+    pub fn write_node_table<F>(
+        &self,
+        writer: impl Write,
+        options: &EdgeListOptions,
+        columns: &[&str],
+        mut values: F,
+    ) -> std::io::Result<()>
+    where
+        F: FnMut(Node<&Data>) -> Vec<String>,
+    {
+        let mut nodes = self.nodes().collect::<Vec<_>>();
+        nodes.sort_unstable_by(|a, b| a.node_id.cmp(&b.node_id));
+
+        let mut out = BufWriter::new(writer);
+        if options.header {
+            options.write_record(
+                &mut out,
+                std::iter::once("id").chain(columns.iter().copied()),
+            )?;
+        }
+        for node in nodes {
+            let node_id = node.id();
+            let values = values(node);
+            options.write_record(
+                &mut out,
+                std::iter::once(node_id.as_ref()).chain(values.iter().map(String::as_str)),
+            )?;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(input: &str, options: EdgeListOptions) -> Result<DirectedGraph<()>, OrbweaverError> {
+        DirectedGraph::from_edge_list(input.as_bytes(), options)
+    }
+
+    #[test]
+    fn test_from_edge_list() {
+        let graph = read(
+            "parent,child\r\na,b\n\n \"a, the first\" , \"say \"\"hi\"\"\"\nb,c\na,b\n",
+            EdgeListOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(graph.n_nodes(), 5);
+        assert!(graph.edge_exists("a", "b"));
+        assert!(graph.edge_exists("a, the first", "say \"hi\""));
+        assert!(graph.edge_exists("b", "c"));
+        assert_eq!(graph.edges().count(), 3);
+    }
+
+    #[test]
+    fn test_from_edge_list_options() {
+        let graph = read(
+            "1\t'x'\ty\n2\tx\t'multi\nline'\n",
+            EdgeListOptions::default()
+                .delimiter('\t')
+                .quote('\'')
+                .header(false)
+                .columns(1, 2),
+        )
+        .unwrap();
+
+        assert!(graph.edge_exists("x", "y"));
+        assert!(graph.edge_exists("x", "multi\nline"));
+        assert!(graph.get_node("1").is_err());
+    }
+
+    #[test]
+    fn test_from_edge_list_errors() {
+        let line = |input: &str| match read(input, EdgeListOptions::default()) {
+            Err(OrbweaverError::EdgeList(EdgeListError::Parse { line, .. })) => line,
+            _ => panic!("Expected a parse error"),
+        };
+
+        assert_eq!(line("parent,child\na,b\nc\n"), 3);
+        assert_eq!(line("parent,child\na,b\n\"c\nd\",\n"), 3);
+        assert_eq!(line("parent,child\na,\"b\n"), 2);
+        assert_eq!(line("parent,child\n\"a\"x,b\n"), 2);
+        assert_eq!(line("\"parent,child\na,b\n"), 1);
+    }
+
+    #[test]
+    fn test_from_edge_list_reads_in_small_chunks() {
+        // A one byte buffer makes every record cross a buffer boundary
+        let input = "parent,child\n\"a\n\"\"b\"\"\",c\r\nc,d\n";
+        let reader = BufReader::with_capacity(1, input.as_bytes());
+        let graph: DirectedGraph<()> =
+            DirectedGraph::from_edge_list(reader, EdgeListOptions::default()).unwrap();
+
+        assert!(graph.edge_exists("a\n\"b\"", "c"));
+        assert!(graph.edge_exists("c", "d"));
+    }
+
+    #[test]
+    fn test_write_edge_list_round_trip() {
+        let mut graph = DirectedGraph::<u32>::new();
+        let _ = graph.add_node("a", 1);
+        let _ = graph.add_node("b,c", 2);
+        let _ = graph.add_node(" lonely ", 3);
+        let _ = graph.add_edge("b,c", "a");
+
+        let mut edges = Vec::new();
+        graph
+            .write_edge_list(&mut edges, &EdgeListOptions::default())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(edges.clone()).unwrap(),
+            "parent,child\n\"b,c\",a\n"
+        );
+
+        let parsed =
+            DirectedGraph::<()>::from_edge_list(edges.as_slice(), EdgeListOptions::default())
+                .unwrap();
+        assert!(parsed.edge_exists("b,c", "a"));
+
+        let mut nodes = Vec::new();
+        graph
+            .write_node_table(
+                &mut nodes,
+                &EdgeListOptions::default().delimiter(';'),
+                &["value"],
+                |node| vec![node.data().to_string()],
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(nodes).unwrap(),
+            "id;value\n\" lonely \";3\na;1\nb,c;2\n"
+        );
+    }
+}
@@ -1,53 +1,297 @@
 use crate::NodeId;
 
+/// Writes the cycle as `a -> b -> c -> a`
+fn write_cycle(f: &mut std::fmt::Formatter<'_>, cycle: &[NodeId]) -> std::fmt::Result {
+    for node_id in cycle {
+        write!(f, "{node_id} -> ")?;
+    }
+    match cycle.first() {
+        Some(first) => write!(f, "{first}"),
+        None => Ok(()),
+    }
+}
+
 #[derive(Debug)]
-pub struct GraphHasCycle;
+pub struct GraphHasCycle {
+    /// One of the cycles in the graph, the first node is not repeated
+    /// at the end
+    pub cycle: Vec<NodeId>,
+    /// Every node that could not be topologically sorted
+    pub unsorted: Vec<NodeId>,
+}
 
 impl std::fmt::Display for GraphHasCycle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Unable to topologically sort, graph has at least one cycle"
-        )
+            "Unable to topologically sort, graph has at least one cycle: "
+        )?;
+        write_cycle(f, &self.cycle)
     }
 }
 
 impl std::error::Error for GraphHasCycle {}
 
-#[derive(Debug)]
-pub struct DuplicateNode(pub NodeId);
+/// Former error of the graph operations, which now return
+/// [`OrbweaverError`]
+#[deprecated(note = "Use `OrbweaverError`")]
+pub type GraphInteractionError = OrbweaverError;
+
+/// Inconsistency between the internal structures of a graph, reported
+/// by [`DirectedGraph::validate`](crate::directed::DirectedGraph::validate)
+/// and [`DirectedAcyclicGraph::validate`](crate::acyclic::DirectedAcyclicGraph::validate)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidGraph {
+    /// The per node rows do not have one entry per node
+    RowCount {
+        rows: usize,
+        nodes: usize,
+    },
+    /// The interner does not map the node to its position
+    StaleIndex(NodeId),
+    /// A child or parent of the node is not a valid index
+    DanglingEdge(NodeId, u32),
+    /// The edge is in the children of `from` but not in the parents of
+    /// `to`, or the other way around
+    AsymmetricEdge(NodeId, NodeId),
+    /// The edge is stored more than once
+    RepeatedEdge(NodeId, NodeId),
+    MissingEdgeData(NodeId, NodeId),
+    /// Edge data is kept for edges of the node that do not exist
+    StaleEdgeData(NodeId),
+    EdgeCount {
+        stored: usize,
+        counted: usize,
+    },
+    /// The topological sort does not hold every node once
+    TopologicalSortSize {
+        sorted: usize,
+        nodes: usize,
+    },
+    /// The cached position of the node is not its place in the sort
+    StalePosition(NodeId),
+    /// The child is placed after its parent in the topological sort
+    EdgeAgainstOrder(NodeId, NodeId),
+}
 
-impl std::fmt::Display for DuplicateNode {
+impl std::fmt::Display for InvalidGraph {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Unable to insert node, `{}` already exists",
-            self.0.as_ref()
-        )
+        match self {
+            Self::RowCount { rows, nodes } => {
+                write!(f, "Graph keeps {rows} rows for {nodes} nodes")
+            }
+            Self::StaleIndex(node_id) => write!(f, "Index of `{node_id}` is out of date"),
+            Self::DanglingEdge(from, to) => {
+                write!(f, "Edge from `{from}` points to missing index {to}")
+            }
+            Self::AsymmetricEdge(from, to) => write!(
+                f,
+                "Edge `{from}` -> `{to}` is not in both the parents and the children"
+            ),
+            Self::RepeatedEdge(from, to) => write!(f, "Edge `{from}` -> `{to}` is stored twice"),
+            Self::MissingEdgeData(from, to) => write!(f, "Edge `{from}` -> `{to}` has no data"),
+            Self::StaleEdgeData(from) => {
+                write!(f, "Data kept for missing edges of `{from}`")
+            }
+            Self::EdgeCount { stored, counted } => {
+                write!(f, "Counted {stored} edges but the graph has {counted}")
+            }
+            Self::TopologicalSortSize { sorted, nodes } => write!(
+                f,
+                "Topological sort holds {sorted} nodes but the graph has {nodes}"
+            ),
+            Self::StalePosition(node_id) => write!(f, "Position of `{node_id}` is out of date"),
+            Self::EdgeAgainstOrder(from, to) => write!(
+                f,
+                "Edge `{from}` -> `{to}` goes against the topological sort"
+            ),
+        }
     }
 }
 
-impl std::error::Error for DuplicateNode {}
+impl std::error::Error for InvalidGraph {}
 
+/// Error returned by every fallible operation of the crate, from graph
+/// mutations to the readers of the supported formats. Errors of a
+/// format keep their own type, wrapped in a variant of this one.
 #[derive(Debug)]
-pub enum GraphInteractionError {
+#[non_exhaustive]
+pub enum OrbweaverError {
     NodeNotExist(NodeId),
+    EdgeNotExist(NodeId, NodeId),
+    DuplicateNode(NodeId),
+    DuplicateEdge(NodeId, NodeId),
+    /// The graph has a cycle, with one of them as witness
+    Cycle(GraphHasCycle),
+    NegativeCycle(Vec<NodeId>),
+    /// The edge was rejected because it would close the contained cycle
+    EdgeCreatesCycle(Vec<NodeId>),
+    Io(std::io::Error),
+    /// The internal structures of the graph disagree
+    Invalid(InvalidGraph),
+    Dot(crate::dot::DotParseError),
+    EdgeList(crate::edge_list::EdgeListError),
+    Binary(crate::binary::BinaryError),
+    NodeLink(crate::node_link::NodeLinkError),
+    #[cfg(feature = "graphml")]
+    GraphMl(crate::graphml::GraphMlError),
 }
 
-impl GraphInteractionError {
+impl OrbweaverError {
     pub(crate) fn node_not_exists(id: impl AsRef<str>) -> Self {
         Self::NodeNotExist(NodeId::from(id.as_ref()))
     }
+    pub(crate) fn edge_not_exists(from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
+        Self::EdgeNotExist(NodeId::from(from.as_ref()), NodeId::from(to.as_ref()))
+    }
 }
 
-impl std::fmt::Display for GraphInteractionError {
+impl std::fmt::Display for OrbweaverError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::NodeNotExist(node_id) => {
-                write!(f, "Node `{}` does not exist", node_id.as_ref())
+            Self::NodeNotExist(node_id) => write!(f, "Node `{node_id}` does not exist"),
+            Self::EdgeNotExist(from, to) => write!(f, "Edge `{from}` -> `{to}` does not exist"),
+            Self::DuplicateNode(node_id) => {
+                write!(f, "Unable to insert node, `{node_id}` already exists")
+            }
+            Self::DuplicateEdge(from, to) => write!(
+                f,
+                "Unable to insert edge, `{from}` -> `{to}` already exists"
+            ),
+            Self::Cycle(err) => err.fmt(f),
+            Self::NegativeCycle(cycle) => {
+                write!(f, "Graph has a negative cycle: ")?;
+                write_cycle(f, cycle)
             }
+            Self::EdgeCreatesCycle(cycle) => {
+                write!(f, "Edge would create a cycle: ")?;
+                write_cycle(f, cycle)
+            }
+            Self::Io(err) => write!(f, "Unable to read or write graph: {err}"),
+            Self::Invalid(err) => write!(f, "Graph is inconsistent: {err}"),
+            Self::Dot(err) => err.fmt(f),
+            Self::EdgeList(err) => err.fmt(f),
+            Self::Binary(err) => err.fmt(f),
+            Self::NodeLink(err) => err.fmt(f),
+            #[cfg(feature = "graphml")]
+            Self::GraphMl(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for OrbweaverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Cycle(err) => Some(err),
+            Self::Io(err) => Some(err),
+            Self::Invalid(err) => Some(err),
+            Self::Dot(err) => Some(err),
+            Self::EdgeList(err) => Some(err),
+            Self::Binary(err) => Some(err),
+            Self::NodeLink(err) => Some(err),
+            #[cfg(feature = "graphml")]
+            Self::GraphMl(err) => Some(err),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for GraphInteractionError {}
+impl From<GraphHasCycle> for OrbweaverError {
+    fn from(value: GraphHasCycle) -> Self {
+        Self::Cycle(value)
+    }
+}
+
+impl From<InvalidGraph> for OrbweaverError {
+    fn from(value: InvalidGraph) -> Self {
+        Self::Invalid(value)
+    }
+}
+
+impl From<std::io::Error> for OrbweaverError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<crate::dot::DotParseError> for OrbweaverError {
+    fn from(value: crate::dot::DotParseError) -> Self {
+        Self::Dot(value)
+    }
+}
+
+impl From<crate::edge_list::EdgeListError> for OrbweaverError {
+    fn from(value: crate::edge_list::EdgeListError) -> Self {
+        Self::EdgeList(value)
+    }
+}
+
+impl From<crate::binary::BinaryError> for OrbweaverError {
+    fn from(value: crate::binary::BinaryError) -> Self {
+        Self::Binary(value)
+    }
+}
+
+impl From<crate::node_link::NodeLinkError> for OrbweaverError {
+    fn from(value: crate::node_link::NodeLinkError) -> Self {
+        Self::NodeLink(value)
+    }
+}
+
+#[cfg(feature = "graphml")]
+impl From<crate::graphml::GraphMlError> for OrbweaverError {
+    fn from(value: crate::graphml::GraphMlError) -> Self {
+        Self::GraphMl(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn build_from_dot(source: &str) -> Result<(), OrbweaverError> {
+        let mut graph = crate::dot::from_dot(source)?;
+        graph.add_node("extra", Default::default())?;
+        graph.add_edge("extra", "a")?;
+        DirectedAcyclicGraph::build(graph)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_convert_into_orbweaver_error() {
+        assert!(build_from_dot("digraph { a -> b }").is_ok());
+        assert!(matches!(
+            build_from_dot("digraph { a -> b; b -> a }"),
+            Err(OrbweaverError::Cycle(err)) if err.cycle.len() == 2
+        ));
+        assert!(matches!(
+            build_from_dot("digraph { extra -> b }"),
+            Err(OrbweaverError::DuplicateNode(node_id)) if node_id == "extra"
+        ));
+        assert!(matches!(
+            build_from_dot("digraph { b -> c }"),
+            Err(OrbweaverError::NodeNotExist(node_id)) if node_id == "a"
+        ));
+        let err = build_from_dot("digraph { a -> }").unwrap_err();
+        assert!(matches!(
+            err,
+            OrbweaverError::Dot(crate::dot::DotParseError { line: 1, .. })
+        ));
+        assert!(err.to_string().starts_with("Unable to parse DOT, line 1: "));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_orbweaver_error_display() {
+        let err = OrbweaverError::DuplicateEdge("a".into(), "b".into());
+        assert_eq!(
+            err.to_string(),
+            "Unable to insert edge, `a` -> `b` already exists"
+        );
+        let err = OrbweaverError::node_not_exists("x");
+        assert_eq!(err.to_string(), "Node `x` does not exist");
+        let err = OrbweaverError::EdgeCreatesCycle(vec!["a".into(), "b".into()]);
+        assert_eq!(err.to_string(), "Edge would create a cycle: a -> b -> a");
+    }
+}
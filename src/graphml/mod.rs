@@ -0,0 +1,644 @@
+//! GraphML reader and writer, enabled with the `graphml` feature.
+//!
+//! Node and edge data is mapped to GraphML `<data>` elements through
+//! the [`GraphMlData`] trait.
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+
+mod xml;
+use xml::{escape, Event, XmlReader};
+
+/// Type of a GraphML attribute, written as `attr.type` in `<key>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GraphMlType {
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+}
+
+impl GraphMlType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Int => "int",
+            Self::Long => "long",
+            Self::Float => "float",
+            Self::Double => "double",
+            Self::String => "string",
+        }
+    }
+}
+
+/// Maps node or edge data to GraphML attributes and back.
+///
+/// Attributes are identified by their `attr.name`, the ids of the
+/// `<key>` elements are handled by the reader and writer.
+pub trait GraphMlData: Sized {
+    /// Attribute names and their values
+    fn to_attributes(&self) -> Vec<(String, String)>;
+    /// Builds the data from the attributes found in the document,
+    /// including the default values declared by the keys
+    fn from_attributes(attributes: HashMap<String, String>) -> Result<Self, String>;
+    /// Type declared for the attribute `name` when writing
+    fn attribute_type(_name: &str) -> GraphMlType {
+        GraphMlType::String
+    }
+}
+
+impl GraphMlData for () {
+    fn to_attributes(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+    fn from_attributes(_attributes: HashMap<String, String>) -> Result<Self, String> {
+        Ok(())
+    }
+}
+
+impl GraphMlData for HashMap<String, String> {
+    fn to_attributes(&self) -> Vec<(String, String)> {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+    fn from_attributes(attributes: HashMap<String, String>) -> Result<Self, String> {
+        Ok(attributes)
+    }
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GraphMlError {
+    Io(std::io::Error),
+    /// The document is not well formed XML
+    Malformed {
+        line: usize,
+        message: String,
+    },
+    /// The document is valid XML but not a graph we can read
+    Invalid {
+        line: usize,
+        message: String,
+    },
+    /// [`GraphMlData::from_attributes`] rejected the data of an element
+    Data {
+        id: String,
+        message: String,
+    },
+}
+
+impl GraphMlError {
+    pub(crate) fn malformed(line: usize, message: impl Into<String>) -> Self {
+        Self::Malformed {
+            line,
+            message: message.into(),
+        }
+    }
+    fn invalid(line: usize, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for GraphMlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Unable to read GraphML: {err}"),
+            Self::Malformed { line, message } => {
+                write!(f, "Malformed GraphML, line {line}: {message}")
+            }
+            Self::Invalid { line, message } => {
+                write!(f, "Invalid GraphML, line {line}: {message}")
+            }
+            Self::Data { id, message } => {
+                write!(f, "Invalid data for `{id}`: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphMlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for GraphMlError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Assigns a key id to every attribute name used by nodes or edges
+fn declare_keys(prefix: &str, attributes: &[Vec<(String, String)>]) -> BTreeMap<String, String> {
+    let mut names = attributes
+        .iter()
+        .flatten()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names.dedup();
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, format!("{prefix}{i}")))
+        .collect()
+}
+
+fn write_data(
+    writer: &mut impl Write,
+    keys: &BTreeMap<String, String>,
+    mut attributes: Vec<(String, String)>,
+) -> std::io::Result<()> {
+    attributes.sort_unstable();
+    for (name, value) in attributes {
+        writeln!(
+            writer,
+            "      <data key=\"{}\">{}</data>",
+            escape(&keys[&name]),
+            escape(&value)
+        )?;
+    }
+    Ok(())
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData>
+where
+    Data: GraphMlData,
+    EdgeData: GraphMlData,
+{
+    /// Writes the graph as a GraphML document. Nodes, edges and keys
+    /// are sorted so the output is stable.
+    pub fn write_graphml(&self, mut writer: impl Write) -> std::io::Result<()> {
+        let mut nodes = self
+            .nodes()
+            .map(|node| (node.id(), node.data().to_attributes()))
+            .collect::<Vec<_>>();
+        nodes.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let mut edges = self
+            .edges()
+            .map(|edge| (edge.from(), edge.to(), edge.data().to_attributes()))
+            .collect::<Vec<_>>();
+        edges.sort_unstable_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        let node_keys = declare_keys(
+            "n",
+            &nodes.iter().map(|(_, a)| a.clone()).collect::<Vec<_>>(),
+        );
+        let edge_keys = declare_keys(
+            "e",
+            &edges.iter().map(|(_, _, a)| a.clone()).collect::<Vec<_>>(),
+        );
+
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            writer,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+        for (domain, keys, attribute_type) in [
+            ("node", &node_keys, Data::attribute_type as fn(&str) -> _),
+            ("edge", &edge_keys, EdgeData::attribute_type),
+        ] {
+            for (name, id) in keys {
+                writeln!(
+                    writer,
+                    "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                    escape(id),
+                    domain,
+                    escape(name),
+                    attribute_type(name).as_str()
+                )?;
+            }
+        }
+        writeln!(writer, "  <graph edgedefault=\"directed\">")?;
+        for (id, attributes) in nodes {
+            if attributes.is_empty() {
+                writeln!(writer, "    <node id=\"{}\"/>", escape(&id))?;
+            } else {
+                writeln!(writer, "    <node id=\"{}\">", escape(&id))?;
+                write_data(&mut writer, &node_keys, attributes)?;
+                writeln!(writer, "    </node>")?;
+            }
+        }
+        for (from, to, attributes) in edges {
+            let (from, to) = (escape(&from), escape(&to));
+            if attributes.is_empty() {
+                writeln!(writer, "    <edge source=\"{from}\" target=\"{to}\"/>")?;
+            } else {
+                writeln!(writer, "    <edge source=\"{from}\" target=\"{to}\">")?;
+                write_data(&mut writer, &edge_keys, attributes)?;
+                writeln!(writer, "    </edge>")?;
+            }
+        }
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Key {
+    name: String,
+    default: Option<String>,
+    /// `node`, `edge`, `graph` or `all`
+    domain: String,
+}
+
+/// Element currently being read, where `<data>` values are stored
+struct Element {
+    id: String,
+    source: Option<(String, String)>,
+    data: HashMap<String, String>,
+    line: usize,
+}
+
+#[derive(Default)]
+struct Document {
+    keys: HashMap<String, Key>,
+    nodes: Vec<Element>,
+    edges: Vec<Element>,
+}
+
+fn required<'a>(
+    attributes: &'a HashMap<String, String>,
+    name: &str,
+    element: &str,
+    line: usize,
+) -> Result<&'a String, GraphMlError> {
+    attributes.get(name).ok_or_else(|| {
+        GraphMlError::invalid(
+            line,
+            format!("`<{element}>` is missing the `{name}` attribute"),
+        )
+    })
+}
+
+fn parse_document(input: impl BufRead) -> Result<Document, GraphMlError> {
+    let mut reader = XmlReader::new(input);
+    let mut document = Document::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut current: Option<(bool, Element)> = None;
+    let mut current_key: Option<(String, Key)> = None;
+    let mut data_key: Option<String> = None;
+    let mut text = String::new();
+    let mut seen_graph = false;
+
+    while let Some(event) = reader.next_event()? {
+        let line = reader.line();
+        match event {
+            Event::Start { name, attributes } => {
+                let parent = path.last().map(String::as_str);
+                match (parent, name.as_str()) {
+                    (None, "graphml") => (),
+                    (None, _) => {
+                        return Err(GraphMlError::invalid(
+                            line,
+                            "Root element must be `<graphml>`",
+                        ))
+                    }
+                    (Some("graphml"), "key") => {
+                        let id = required(&attributes, "id", "key", line)?.clone();
+                        let key = Key {
+                            name: attributes.get("attr.name").cloned().unwrap_or(id.clone()),
+                            default: None,
+                            domain: attributes.get("for").cloned().unwrap_or("all".to_string()),
+                        };
+                        current_key = Some((id, key));
+                    }
+                    (Some("key"), "default") => text.clear(),
+                    (Some("graphml"), "graph") => {
+                        if seen_graph {
+                            return Err(GraphMlError::invalid(
+                                line,
+                                "Only one `<graph>` is supported",
+                            ));
+                        }
+                        seen_graph = true;
+                        if attributes.get("edgedefault").map(String::as_str) == Some("undirected") {
+                            return Err(GraphMlError::invalid(
+                                line,
+                                "Undirected graphs are not supported",
+                            ));
+                        }
+                    }
+                    (Some("graph"), "node") => {
+                        let id = required(&attributes, "id", "node", line)?.clone();
+                        current = Some((
+                            true,
+                            Element {
+                                id,
+                                source: None,
+                                data: HashMap::new(),
+                                line,
+                            },
+                        ));
+                    }
+                    (Some("graph"), "edge") => {
+                        if attributes.get("directed").map(String::as_str) == Some("false") {
+                            return Err(GraphMlError::invalid(
+                                line,
+                                "Undirected edges are not supported",
+                            ));
+                        }
+                        let source = required(&attributes, "source", "edge", line)?.clone();
+                        let target = required(&attributes, "target", "edge", line)?.clone();
+                        current = Some((
+                            false,
+                            Element {
+                                id: attributes
+                                    .get("id")
+                                    .cloned()
+                                    .unwrap_or_else(|| format!("{source} -> {target}")),
+                                source: Some((source, target)),
+                                data: HashMap::new(),
+                                line,
+                            },
+                        ));
+                    }
+                    (Some("node"), "data") | (Some("edge"), "data") => {
+                        data_key = Some(required(&attributes, "key", "data", line)?.clone());
+                        text.clear();
+                    }
+                    (Some("node"), "graph") => {
+                        return Err(GraphMlError::invalid(
+                            line,
+                            "Nested graphs are not supported",
+                        ))
+                    }
+                    (Some("graph"), "hyperedge") => {
+                        return Err(GraphMlError::invalid(line, "Hyperedges are not supported"))
+                    }
+                    // Anything else, like `<desc>`, `<port>` or graph
+                    // level `<data>`, is ignored along with its content
+                    _ => (),
+                }
+                path.push(name);
+            }
+            Event::Text(value) => text.push_str(&value),
+            Event::End { name } => {
+                path.pop();
+                match name.as_str() {
+                    "default" if path.last().map(String::as_str) == Some("key") => {
+                        if let Some((_, key)) = current_key.as_mut() {
+                            key.default = Some(std::mem::take(&mut text));
+                        }
+                    }
+                    "key" => {
+                        if let Some((id, key)) = current_key.take() {
+                            document.keys.insert(id, key);
+                        }
+                    }
+                    "data" => {
+                        if let (Some(key), Some((_, element))) = (data_key.take(), current.as_mut())
+                        {
+                            element.data.insert(key, std::mem::take(&mut text));
+                        }
+                    }
+                    "node" | "edge" if path.last().map(String::as_str) == Some("graph") => {
+                        if let Some((is_node, element)) = current.take() {
+                            if is_node {
+                                document.nodes.push(element);
+                            } else {
+                                document.edges.push(element);
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    if !seen_graph {
+        return Err(GraphMlError::invalid(
+            reader.line(),
+            "Missing `<graph>` element",
+        ));
+    }
+
+    Ok(document)
+}
+
+/// Resolves key ids to attribute names, filling in the defaults
+fn resolve<D: GraphMlData>(
+    element: Element,
+    keys: &HashMap<String, Key>,
+    domain: &str,
+) -> Result<(Element, D), GraphMlError> {
+    let mut attributes = HashMap::new();
+    for key in keys.values() {
+        if key.domain == domain || key.domain == "all" {
+            if let Some(default) = &key.default {
+                attributes.insert(key.name.clone(), default.clone());
+            }
+        }
+    }
+    for (id, value) in &element.data {
+        let key = keys
+            .get(id)
+            .ok_or_else(|| GraphMlError::invalid(element.line, format!("Undeclared key `{id}`")))?;
+        attributes.insert(key.name.clone(), value.clone());
+    }
+    let data = D::from_attributes(attributes).map_err(|message| GraphMlError::Data {
+        id: element.id.clone(),
+        message,
+    })?;
+    Ok((element, data))
+}
+
+/// Reads a GraphML document with a single directed graph.
+///
+/// Edges may reference nodes declared later in the document. Ports,
+/// descriptions and graph level data are ignored while nested graphs,
+/// hyperedges and undirected edges are reported as
+/// [`GraphMlError::Invalid`].
+pub fn read_graphml<Data, EdgeData>(
+    reader: impl Read,
+) -> Result<DirectedGraph<Data, EdgeData>, OrbweaverError>
+where
+    Data: GraphMlData,
+    EdgeData: GraphMlData,
+{
+    let document = parse_document(BufReader::new(reader))?;
+
+    let mut graph = DirectedGraph::new();
+    for element in document.nodes {
+        let (element, data) = resolve::<Data>(element, &document.keys, "node")?;
+        if graph.add_node(&element.id, data).is_err() {
+            return Err(GraphMlError::invalid(
+                element.line,
+                format!("Duplicate node `{}`", element.id),
+            )
+            .into());
+        }
+    }
+    for element in document.edges {
+        let (element, data) = resolve::<EdgeData>(element, &document.keys, "edge")?;
+        let (source, target) = element.source.as_ref().expect("Edges have a source");
+        if graph.add_edge_with(source, target, data).is_err() {
+            return Err(GraphMlError::invalid(
+                element.line,
+                format!(
+                    "Edge `{}` references a node that does not exist",
+                    element.id
+                ),
+            )
+            .into());
+        }
+    }
+
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Person {
+        name: String,
+        age: u32,
+    }
+
+    impl GraphMlData for Person {
+        fn to_attributes(&self) -> Vec<(String, String)> {
+            vec![
+                ("name".to_string(), self.name.clone()),
+                ("age".to_string(), self.age.to_string()),
+            ]
+        }
+        fn from_attributes(attributes: HashMap<String, String>) -> Result<Self, String> {
+            let name = attributes.get("name").cloned().ok_or("Missing name")?;
+            let age = attributes
+                .get("age")
+                .ok_or("Missing age")?
+                .parse()
+                .map_err(|_| "Age must be a number")?;
+            Ok(Person { name, age })
+        }
+        fn attribute_type(name: &str) -> GraphMlType {
+            match name {
+                "age" => GraphMlType::Int,
+                _ => GraphMlType::String,
+            }
+        }
+    }
+
+    type Attributes = HashMap<String, String>;
+
+    fn read(input: &str) -> Result<DirectedGraph<Attributes, Attributes>, OrbweaverError> {
+        read_graphml(input.as_bytes())
+    }
+
+    #[test]
+    fn test_graphml_round_trip() {
+        let mut graph = DirectedGraph::<Person, ()>::new();
+        let _ = graph.add_node(
+            "a",
+            Person {
+                name: "Ana <admin> & co".to_string(),
+                age: 30,
+            },
+        );
+        let _ = graph.add_node(
+            "b",
+            Person {
+                name: "Bo".to_string(),
+                age: 41,
+            },
+        );
+        let _ = graph.add_edge("a", "b");
+
+        let mut out = Vec::new();
+        graph.write_graphml(&mut out).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.contains("attr.name=\"age\" attr.type=\"int\""));
+
+        let parsed: DirectedGraph<Person, ()> = read_graphml(written.as_bytes()).unwrap();
+        assert_eq!(parsed.n_nodes(), 2);
+        assert_eq!(
+            parsed.get_node("a").unwrap().data().name,
+            "Ana <admin> & co"
+        );
+        assert_eq!(parsed.get_node("b").unwrap().data().age, 41);
+        assert!(parsed.edge_exists("a", "b"));
+
+        let mut again = Vec::new();
+        parsed.write_graphml(&mut again).unwrap();
+        assert_eq!(String::from_utf8(again).unwrap(), written);
+    }
+
+    #[test]
+    fn test_read_graphml_with_defaults() {
+        let graph = read(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+              <key id="d0" for="node" attr.name="color" attr.type="string">
+                <default>yellow</default>
+              </key>
+              <key id="d1" for="edge" attr.name="weight" attr.type="double"/>
+              <graph id="G" edgedefault="directed">
+                <desc>Ignored</desc>
+                <edge source="n0" target="n1"><data key="d1">1.5</data></edge>
+                <node id="n0"><data key="d0">green</data></node>
+                <node id="n1"/>
+              </graph>
+            </graphml>"#,
+        )
+        .unwrap();
+
+        assert_eq!(graph.get_node("n0").unwrap().data()["color"], "green");
+        assert_eq!(graph.get_node("n1").unwrap().data()["color"], "yellow");
+        assert_eq!(graph.get_edge("n0", "n1").unwrap().data()["weight"], "1.5");
+    }
+
+    #[test]
+    fn test_read_graphml_errors() {
+        assert!(matches!(
+            read("<graphml><graph><node id=\"a\"></graph></graphml>"),
+            Err(OrbweaverError::GraphMl(GraphMlError::Malformed { .. }))
+        ));
+        assert!(matches!(
+            read("<graphml><graph edgedefault=\"undirected\"/></graphml>"),
+            Err(OrbweaverError::GraphMl(GraphMlError::Invalid { .. }))
+        ));
+        assert!(matches!(
+            read("<graphml><graph><node/></graph></graphml>"),
+            Err(OrbweaverError::GraphMl(GraphMlError::Invalid { .. }))
+        ));
+        assert!(matches!(
+            read("<graphml><graph><node id=\"a\"/><node id=\"a\"/></graph></graphml>"),
+            Err(OrbweaverError::GraphMl(GraphMlError::Invalid { .. }))
+        ));
+        assert!(matches!(
+            read("<graphml><graph><edge source=\"a\" target=\"b\"/></graph></graphml>"),
+            Err(OrbweaverError::GraphMl(GraphMlError::Invalid { .. }))
+        ));
+        assert!(matches!(
+            read(
+                "<graphml><graph><node id=\"a\"><data key=\"x\">1</data></node></graph></graphml>"
+            ),
+            Err(OrbweaverError::GraphMl(GraphMlError::Invalid { .. }))
+        ));
+
+        let err = read_graphml::<Person, ()>(
+            "<graphml><key id=\"k\" for=\"node\" attr.name=\"name\"/><graph>\n<node id=\"a\"><data key=\"k\">Ana</data></node></graph></graphml>".as_bytes(),
+        )
+        .unwrap_err();
+        match err {
+            OrbweaverError::GraphMl(GraphMlError::Data { id, message }) => {
+                assert_eq!(id, "a");
+                assert_eq!(message, "Missing age");
+            }
+            _ => panic!("Expected a data error"),
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! Minimal pull parser covering the subset of XML used by GraphML
+//! documents: elements, attributes, text, CDATA, comments, processing
+//! instructions and the predefined and numeric entities.
+use super::GraphMlError;
+use std::collections::HashMap;
+use std::io::BufRead;
+
+#[derive(Debug, PartialEq)]
+pub(super) enum Event {
+    Start {
+        name: String,
+        attributes: HashMap<String, String>,
+    },
+    End {
+        name: String,
+    },
+    Text(String),
+}
+
+pub(super) struct XmlReader<R> {
+    reader: R,
+    /// Input read so far, consumed up to `position`
+    buffer: String,
+    position: usize,
+    eof: bool,
+    line: usize,
+    open: Vec<String>,
+    /// End event queued by a self closing element
+    pending_end: Option<String>,
+}
+
+/// Drops the namespace prefix, GraphML files may use `<g:node>`
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+pub(super) fn unescape(value: &str, line: usize) -> Result<String, GraphMlError> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| GraphMlError::malformed(line, "Unterminated entity"))?
+            + start;
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(char::from_u32).ok_or_else(|| {
+                    GraphMlError::malformed(line, format!("Unknown entity `&{entity};`"))
+                })?
+            }
+        };
+        result.push(c);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+pub(super) fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '&' => result.push_str("&amp;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+impl<R: BufRead> XmlReader<R> {
+    pub(super) fn new(reader: R) -> Self {
+        XmlReader {
+            reader,
+            buffer: String::new(),
+            position: 0,
+            eof: false,
+            line: 1,
+            open: Vec::new(),
+            pending_end: None,
+        }
+    }
+
+    pub(super) fn line(&self) -> usize {
+        self.line
+    }
+
+    fn rest(&self) -> &str {
+        &self.buffer[self.position..]
+    }
+
+    /// Reads another line of input, dropping the consumed part of the
+    /// buffer. Returns `false` at the end of the input.
+    fn fill(&mut self) -> Result<bool, GraphMlError> {
+        if self.eof {
+            return Ok(false);
+        }
+        self.buffer.drain(..self.position);
+        self.position = 0;
+        if self.reader.read_line(&mut self.buffer)? == 0 {
+            self.eof = true;
+        }
+        Ok(!self.eof)
+    }
+
+    /// Buffers at least `len` bytes unless the input ends first
+    fn fill_to(&mut self, len: usize) -> Result<(), GraphMlError> {
+        while self.rest().len() < len && self.fill()? {}
+        Ok(())
+    }
+
+    /// Buffers up to the `>` closing the current tag, skipping over
+    /// quoted attribute values
+    fn fill_tag(&mut self) -> Result<(), GraphMlError> {
+        let mut searched = 0;
+        let mut quote = None;
+        loop {
+            for &byte in &self.rest().as_bytes()[searched..] {
+                match (quote, byte) {
+                    (None, b'>') => return Ok(()),
+                    (None, b'"' | b'\'') => quote = Some(byte),
+                    (Some(open), _) if open == byte => quote = None,
+                    _ => (),
+                }
+            }
+            searched = self.rest().len();
+            if !self.fill()? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Offset in the unconsumed input of the next `pattern`, reading
+    /// more input until it shows up
+    fn find(&mut self, pattern: &str) -> Result<Option<usize>, GraphMlError> {
+        let mut searched = 0;
+        loop {
+            if let Some(offset) = self.rest()[searched..].find(pattern) {
+                return Ok(Some(searched + offset));
+            }
+            // The pattern may start in the part already searched
+            searched = self.rest().len().saturating_sub(pattern.len() - 1);
+            while !self.rest().is_char_boundary(searched) {
+                searched -= 1;
+            }
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn advance(&mut self, len: usize) -> &str {
+        let consumed = &self.buffer[self.position..self.position + len];
+        self.line += consumed.matches('\n').count();
+        self.position += len;
+        consumed
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        let len = rest.len() - rest.trim_start().len();
+        self.advance(len);
+    }
+
+    /// Consumes everything up to and including `terminator`
+    fn skip_past(&mut self, terminator: &str, what: &str) -> Result<String, GraphMlError> {
+        match self.find(terminator)? {
+            Some(end) => {
+                let content = self.advance(end).to_string();
+                self.advance(terminator.len());
+                Ok(content)
+            }
+            None => Err(GraphMlError::malformed(
+                self.line,
+                format!("Unterminated {what}"),
+            )),
+        }
+    }
+
+    pub(super) fn next_event(&mut self) -> Result<Option<Event>, GraphMlError> {
+        if let Some(name) = self.pending_end.take() {
+            return Ok(Some(Event::End { name }));
+        }
+
+        loop {
+            // Enough for the longest markup checked below, `<![CDATA[`
+            self.fill_to(9)?;
+            let rest = self.rest();
+            if rest.is_empty() {
+                if let Some(name) = self.open.last() {
+                    return Err(GraphMlError::malformed(
+                        self.line,
+                        format!("Element `{name}` is never closed"),
+                    ));
+                }
+                return Ok(None);
+            }
+
+            if rest.starts_with("<!--") {
+                self.advance(4);
+                self.skip_past("-->", "comment")?;
+            } else if rest.starts_with("<?") {
+                self.advance(2);
+                self.skip_past("?>", "processing instruction")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.advance(9);
+                let text = self.skip_past("]]>", "CDATA section")?;
+                return Ok(Some(Event::Text(text)));
+            } else if rest.starts_with("<!") {
+                self.advance(2);
+                self.skip_past(">", "declaration")?;
+            } else if rest.starts_with("</") {
+                self.advance(2);
+                let line = self.line;
+                let name = self.skip_past(">", "closing tag")?;
+                let name = local_name(name.trim()).to_string();
+                match self.open.pop() {
+                    Some(open) if open == name => return Ok(Some(Event::End { name })),
+                    Some(open) => {
+                        return Err(GraphMlError::malformed(
+                            line,
+                            format!("Expected `</{open}>`, found `</{name}>`"),
+                        ))
+                    }
+                    None => {
+                        return Err(GraphMlError::malformed(
+                            line,
+                            format!("Unexpected `</{name}>`"),
+                        ))
+                    }
+                }
+            } else if rest.starts_with('<') {
+                self.advance(1);
+                return self.start_element().map(Some);
+            } else {
+                let end = match self.find("<")? {
+                    Some(end) => end,
+                    None => self.rest().len(),
+                };
+                let line = self.line;
+                let outside_root = self.open.is_empty();
+                let text = self.advance(end);
+                if outside_root {
+                    if !text.trim().is_empty() {
+                        return Err(GraphMlError::malformed(
+                            line,
+                            "Text outside of the root element",
+                        ));
+                    }
+                    continue;
+                }
+                let text = unescape(text, line)?;
+                return Ok(Some(Event::Text(text)));
+            }
+        }
+    }
+
+    fn start_element(&mut self) -> Result<Event, GraphMlError> {
+        let line = self.line;
+        self.fill_tag()?;
+        let rest = self.rest();
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        if name_len == 0 {
+            return Err(GraphMlError::malformed(line, "Expected an element name"));
+        }
+        let name = local_name(self.advance(name_len)).to_string();
+
+        let mut attributes = HashMap::new();
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+
+            if rest.starts_with("/>") {
+                self.advance(2);
+                self.pending_end = Some(name.clone());
+                break;
+            }
+            if rest.starts_with('>') {
+                self.advance(1);
+                self.open.push(name.clone());
+                break;
+            }
+            if rest.is_empty() {
+                return Err(GraphMlError::malformed(
+                    line,
+                    format!("Unterminated element `{name}`"),
+                ));
+            }
+
+            let key_len = rest
+                .find(|c: char| c.is_whitespace() || c == '=')
+                .unwrap_or(rest.len());
+            let key = local_name(self.advance(key_len)).to_string();
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(GraphMlError::malformed(
+                    self.line,
+                    format!("Expected `=` after attribute `{key}`"),
+                ));
+            }
+            self.advance(1);
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => {
+                    return Err(GraphMlError::malformed(
+                        self.line,
+                        format!("Expected a quoted value for attribute `{key}`"),
+                    ))
+                }
+            };
+            self.advance(1);
+            let value_line = self.line;
+            let value = self.skip_past(&quote.to_string(), "attribute value")?;
+            attributes.insert(key, unescape(&value, value_line)?);
+        }
+
+        Ok(Event::Start { name, attributes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(input: &str) -> Result<Vec<Event>, GraphMlError> {
+        let mut reader = XmlReader::new(input.as_bytes());
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event()? {
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    #[test]
+    fn test_xml_events() {
+        let events = events(
+            "<?xml version=\"1.0\"?>\n<!-- comment -->\n<a x='1 &amp; 2'><b/>t&lt;<![CDATA[<raw>]]></a>",
+        )
+        .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Start {
+                    name: "a".to_string(),
+                    attributes: [("x".to_string(), "1 & 2".to_string())].into(),
+                },
+                Event::Start {
+                    name: "b".to_string(),
+                    attributes: HashMap::new(),
+                },
+                Event::End {
+                    name: "b".to_string()
+                },
+                Event::Text("t<".to_string()),
+                Event::Text("<raw>".to_string()),
+                Event::End {
+                    name: "a".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xml_events_across_reads() {
+        let input = "<a\nx='>\n'><!--\n<b>\n-->t\n&amp;\n<![CDATA[\n]]></a\n>";
+        let mut reader = XmlReader::new(std::io::BufReader::with_capacity(1, input.as_bytes()));
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event().unwrap() {
+            events.push(event);
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                Event::Start {
+                    name: "a".to_string(),
+                    attributes: [("x".to_string(), ">\n".to_string())].into(),
+                },
+                Event::Text("t\n&\n".to_string()),
+                Event::Text("\n".to_string()),
+                Event::End {
+                    name: "a".to_string()
+                },
+            ]
+        );
+        assert_eq!(reader.line(), 9);
+    }
+
+    #[test]
+    fn test_xml_malformed() {
+        assert!(events("<a><b></a>").is_err());
+        assert!(events("<a>").is_err());
+        assert!(events("<a x=1></a>").is_err());
+        assert!(events("<a>&unknown;</a>").is_err());
+        assert!(events("<a></a></b>").is_err());
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        let value = "<a href=\"x\">'&'</a>";
+        assert_eq!(unescape(&escape(value), 1).unwrap(), value);
+    }
+}
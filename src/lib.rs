@@ -1,42 +1,69 @@
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{ops::Deref, rc::Rc};
+use std::ops::Deref;
+
+/// Shared string behind every [`NodeId`]. With the `sync` feature it
+/// is atomically reference counted, making graphs `Send` and `Sync`.
+#[cfg(not(feature = "sync"))]
+type SharedStr = std::rc::Rc<str>;
+#[cfg(feature = "sync")]
+type SharedStr = std::sync::Arc<str>;
 
 pub mod acyclic;
+pub mod algo;
+pub mod binary;
 pub mod directed;
+pub mod dot;
+pub mod edge_list;
 pub mod error;
+#[cfg(feature = "graphml")]
+pub mod graphml;
+pub mod mermaid;
+pub mod node_link;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(all(test, debug_assertions))]
+mod testing;
+pub mod view;
+pub mod visit;
 
 /// Prelude of data types and functionality.
 pub mod prelude {
-    pub(crate) type GraphInteractionResult<T> = Result<T, GraphInteractionError>;
-    pub use crate::acyclic::DirectedAcyclicGraph;
+    pub(crate) type GraphInteractionResult<T> = Result<T, OrbweaverError>;
+    pub use crate::acyclic::{CriticalPath, DirectedAcyclicGraph, NodeSchedule};
     pub use crate::directed::DirectedGraph;
+    pub use crate::directed::OnDuplicateEdge;
+    pub use crate::directed::WeightedPath;
     pub use crate::error::*;
+    pub use crate::view::GraphView;
+    pub use crate::visit::Direction;
+    pub use crate::Edge;
     pub use crate::Graph;
     pub use crate::Node;
     pub use crate::NodeId;
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub enum Graph<Data> {
-    Directed(directed::DirectedGraph<Data>),
-    DirectedAcyclic(acyclic::DirectedAcyclicGraph<Data>),
+pub enum Graph<Data, EdgeData = ()> {
+    Directed(directed::DirectedGraph<Data, EdgeData>),
+    DirectedAcyclic(acyclic::DirectedAcyclicGraph<Data, EdgeData>),
 }
 
-impl<Data> From<acyclic::DirectedAcyclicGraph<Data>> for Graph<Data> {
-    fn from(v: acyclic::DirectedAcyclicGraph<Data>) -> Self {
+impl<Data, EdgeData> From<acyclic::DirectedAcyclicGraph<Data, EdgeData>> for Graph<Data, EdgeData> {
+    fn from(v: acyclic::DirectedAcyclicGraph<Data, EdgeData>) -> Self {
         Self::DirectedAcyclic(v)
     }
 }
 
-impl<Data> From<directed::DirectedGraph<Data>> for Graph<Data> {
-    fn from(v: directed::DirectedGraph<Data>) -> Self {
+impl<Data, EdgeData> From<directed::DirectedGraph<Data, EdgeData>> for Graph<Data, EdgeData> {
+    fn from(v: directed::DirectedGraph<Data, EdgeData>) -> Self {
         Self::Directed(v)
     }
 }
 
 #[allow(clippy::result_large_err)]
-impl<Data> Graph<Data> {
-    pub fn try_into_directed(self) -> Result<directed::DirectedGraph<Data>, Self> {
+impl<Data, EdgeData> Graph<Data, EdgeData> {
+    pub fn try_into_directed(self) -> Result<directed::DirectedGraph<Data, EdgeData>, Self> {
         if let Self::Directed(v) = self {
             Ok(v)
         } else {
@@ -44,7 +71,9 @@ impl<Data> Graph<Data> {
         }
     }
 
-    pub fn try_into_directed_acyclic(self) -> Result<acyclic::DirectedAcyclicGraph<Data>, Self> {
+    pub fn try_into_directed_acyclic(
+        self,
+    ) -> Result<acyclic::DirectedAcyclicGraph<Data, EdgeData>, Self> {
         if let Self::DirectedAcyclic(v) = self {
             Ok(v)
         } else {
@@ -71,7 +100,7 @@ impl<Data> Graph<Data> {
 
 #[derive(Ord, PartialOrd, Eq, PartialEq, Debug, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct NodeId(Rc<str>);
+pub struct NodeId(SharedStr);
 
 impl std::fmt::Display for NodeId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -123,13 +152,13 @@ impl std::cmp::PartialOrd<str> for NodeId {
 
 impl From<&str> for NodeId {
     fn from(value: &str) -> Self {
-        NodeId(Rc::from(value))
+        NodeId(SharedStr::from(value))
     }
 }
 
 impl Clone for NodeId {
     fn clone(&self) -> Self {
-        NodeId(Rc::clone(&self.0))
+        NodeId(SharedStr::clone(&self.0))
     }
 }
 
@@ -188,3 +217,101 @@ where
         }
     }
 }
+
+pub struct Edge<Data> {
+    from: NodeId,
+    to: NodeId,
+    data: Data,
+}
+
+impl<Data> Edge<Data> {
+    #[inline(always)]
+    fn new(from: NodeId, to: NodeId, data: Data) -> Self {
+        Edge { from, to, data }
+    }
+    #[inline(always)]
+    pub fn from(&self) -> NodeId {
+        self.from.clone()
+    }
+    #[inline(always)]
+    pub fn to(&self) -> NodeId {
+        self.to.clone()
+    }
+    #[inline(always)]
+    pub fn data(&self) -> &Data {
+        &self.data
+    }
+    #[inline(always)]
+    pub fn data_mut(&mut self) -> &mut Data {
+        &mut self.data
+    }
+}
+
+impl<Data> Edge<&Data>
+where
+    Data: Clone,
+{
+    #[inline(always)]
+    pub fn cloned(self) -> Edge<Data> {
+        Edge {
+            data: self.data.clone(),
+            from: self.from,
+            to: self.to,
+        }
+    }
+}
+
+impl<Data> Clone for Edge<Data>
+where
+    Data: Clone,
+{
+    fn clone(&self) -> Self {
+        Edge {
+            data: self.data.clone(),
+            from: self.from.clone(),
+            to: self.to.clone(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod tests {
+    use crate::prelude::*;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_graphs_are_send_and_sync() {
+        assert_send_sync::<NodeId>();
+        assert_send_sync::<Node<&String>>();
+        assert_send_sync::<DirectedGraph<String, f64>>();
+        assert_send_sync::<DirectedAcyclicGraph<String, f64>>();
+        assert_send_sync::<Graph<String, f64>>();
+    }
+
+    #[test]
+    fn test_share_dag_across_threads() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("0", ());
+        let _ = graph.add_node("1", ());
+        let _ = graph.add_node("2", ());
+        let _ = graph.add_path(&["0", "1", "2"]);
+        let dag = DirectedAcyclicGraph::build(graph).unwrap();
+
+        let leaves = std::thread::scope(|scope| {
+            let handles = ["0", "1"].map(|root| {
+                let dag = &dag;
+                scope.spawn(move || dag.get_leaves_under(&[root]).unwrap())
+            });
+            handles.map(|handle| handle.join().unwrap())
+        });
+
+        assert_eq!(leaves, [vec!["2"], vec!["2"]]);
+
+        let node_id = dag.get_node_id("1").unwrap();
+        let moved = std::thread::spawn(move || node_id.to_string())
+            .join()
+            .unwrap();
+        assert_eq!(moved, "1");
+    }
+}
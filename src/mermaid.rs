@@ -0,0 +1,318 @@
+//! Mermaid flowchart rendering.
+//!
+//! Node ids that are not valid Mermaid identifiers are replaced by
+//! generated ones and the original id is kept as the label.
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+/// Direction of the flowchart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MermaidDirection {
+    /// Top to bottom, `TD`
+    #[default]
+    TopDown,
+    /// Bottom to top, `BT`
+    BottomUp,
+    /// Left to right, `LR`
+    LeftRight,
+    /// Right to left, `RL`
+    RightLeft,
+}
+
+impl MermaidDirection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::TopDown => "TD",
+            Self::BottomUp => "BT",
+            Self::LeftRight => "LR",
+            Self::RightLeft => "RL",
+        }
+    }
+}
+
+type NodeFn<'a, Data> = Box<dyn FnMut(Node<&Data>) -> Option<String> + 'a>;
+type EdgeFn<'a, EdgeData> = Box<dyn FnMut(Edge<&EdgeData>) -> Option<String> + 'a>;
+
+/// Options for [`DirectedGraph::to_mermaid_with`]
+pub struct MermaidOptions<'a, Data, EdgeData> {
+    direction: MermaidDirection,
+    node_label: Option<NodeFn<'a, Data>>,
+    edge_label: Option<EdgeFn<'a, EdgeData>>,
+    group: Option<NodeFn<'a, Data>>,
+}
+
+impl<Data, EdgeData> Default for MermaidOptions<'_, Data, EdgeData> {
+    fn default() -> Self {
+        MermaidOptions {
+            direction: MermaidDirection::default(),
+            node_label: None,
+            edge_label: None,
+            group: None,
+        }
+    }
+}
+
+impl<'a, Data, EdgeData> MermaidOptions<'a, Data, EdgeData> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn direction(mut self, direction: MermaidDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Label of every node, nodes without a label show their id
+    pub fn node_label(mut self, label: impl FnMut(Node<&Data>) -> Option<String> + 'a) -> Self {
+        self.node_label = Some(Box::new(label));
+        self
+    }
+
+    /// Label of every edge, edges without a label are plain arrows
+    pub fn edge_label(mut self, label: impl FnMut(Edge<&EdgeData>) -> Option<String> + 'a) -> Self {
+        self.edge_label = Some(Box::new(label));
+        self
+    }
+
+    /// Places every node in the subgraph named by `group`, nodes
+    /// without a group are left at the top level
+    pub fn group_by(mut self, group: impl FnMut(Node<&Data>) -> Option<String> + 'a) -> Self {
+        self.group = Some(Box::new(group));
+        self
+    }
+}
+
+/// Mermaid keywords that break the flowchart when used as ids
+const KEYWORDS: [&str; 9] = [
+    "end",
+    "graph",
+    "flowchart",
+    "subgraph",
+    "style",
+    "class",
+    "classDef",
+    "click",
+    "linkStyle",
+];
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !KEYWORDS
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(id))
+}
+
+/// Quotes a label, `"` is written with Mermaid's entity code
+fn quote(label: &str) -> String {
+    format!("\"{}\"", label.replace('"', "#quot;"))
+}
+
+/// Assigns every node and subgraph an identifier Mermaid accepts,
+/// keeping the original id whenever possible
+#[derive(Default)]
+struct Identifiers {
+    used: HashSet<String>,
+    next: usize,
+}
+
+impl Identifiers {
+    fn new(node_ids: &[&NodeId]) -> Self {
+        Identifiers {
+            used: node_ids
+                .iter()
+                .filter(|node_id| is_valid_id(node_id))
+                .map(|node_id| node_id.to_string())
+                .collect(),
+            next: 0,
+        }
+    }
+
+    fn generate(&mut self, prefix: &str) -> String {
+        loop {
+            let id = format!("{prefix}{}", self.next);
+            self.next += 1;
+            if self.used.insert(id.clone()) {
+                return id;
+            }
+        }
+    }
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// Renders the graph as a top down Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        self.to_mermaid_with(MermaidOptions::new())
+    }
+
+    /// Renders the graph as a Mermaid flowchart. Nodes and edges are
+    /// sorted by id so the output is stable.
+    pub fn to_mermaid_with(&self, options: MermaidOptions<'_, Data, EdgeData>) -> String {
+        let mut node_ids = self.ids.ids().iter().collect::<Vec<_>>();
+        node_ids.sort_unstable();
+        self.mermaid(node_ids, options)
+    }
+
+    /// Renders the nodes in the given order, edges follow the order of
+    /// their parent and then of their child
+    pub(crate) fn mermaid(
+        &self,
+        node_ids: Vec<&NodeId>,
+        mut options: MermaidOptions<'_, Data, EdgeData>,
+    ) -> String {
+        let mut identifiers = Identifiers::new(&node_ids);
+        let positions = node_ids
+            .iter()
+            .enumerate()
+            .map(|(position, node_id)| (*node_id, position))
+            .collect::<HashMap<_, _>>();
+
+        let mut declarations = Vec::with_capacity(node_ids.len());
+        let mut ids = HashMap::with_capacity(node_ids.len());
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        let mut ungrouped = Vec::new();
+        for node_id in &node_ids {
+            let node = self.node_unchecked(node_id);
+            let label = options.node_label.as_mut().and_then(|label| label(node));
+            let id = match is_valid_id(node_id) {
+                true => node_id.to_string(),
+                false => identifiers.generate("node_"),
+            };
+            let declaration = match label {
+                Some(label) => format!("{id}[{}]", quote(&label)),
+                None if id != node_id.as_ref() => format!("{id}[{}]", quote(node_id)),
+                None => id.clone(),
+            };
+            let group = options
+                .group
+                .as_mut()
+                .and_then(|group| group(self.node_unchecked(node_id)));
+            match group {
+                Some(group) => groups.entry(group).or_default().push(declarations.len()),
+                None => ungrouped.push(declarations.len()),
+            }
+            declarations.push(declaration);
+            ids.insert(*node_id, id);
+        }
+
+        let mut out = format!("flowchart {}\n", options.direction.as_str());
+        for index in ungrouped {
+            let _ = writeln!(out, "    {}", declarations[index]);
+        }
+        for (group, members) in groups {
+            let id = match is_valid_id(&group) && !identifiers.used.contains(&group) {
+                true => {
+                    identifiers.used.insert(group.clone());
+                    group.clone()
+                }
+                false => identifiers.generate("group_"),
+            };
+            let _ = writeln!(out, "    subgraph {id}[{}]", quote(&group));
+            for index in members {
+                let _ = writeln!(out, "        {}", declarations[index]);
+            }
+            out.push_str("    end\n");
+        }
+
+        let mut edges = self.edges().collect::<Vec<_>>();
+        edges.sort_unstable_by_key(|edge| (positions[&edge.from], positions[&edge.to]));
+        for edge in edges {
+            let (from, to) = (&ids[&edge.from], &ids[&edge.to]);
+            let label = options.edge_label.as_mut().and_then(|label| label(edge));
+            let _ = match label {
+                Some(label) => writeln!(out, "    {from} -->|{}| {to}", quote(&label)),
+                None => writeln!(out, "    {from} --> {to}"),
+            };
+        }
+
+        out
+    }
+}
+
+impl<Data, EdgeData> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Renders the graph as a top down Mermaid flowchart with the
+    /// nodes in topological order, sources first
+    pub fn to_mermaid(&self) -> String {
+        self.to_mermaid_with(MermaidOptions::new())
+    }
+
+    /// Same as [`DirectedGraph::to_mermaid_with`] but nodes and edges
+    /// follow the topological order, sources first
+    pub fn to_mermaid_with(&self, options: MermaidOptions<'_, Data, EdgeData>) -> String {
+        self.dg
+            .mermaid(self.topological_sort.iter().rev().collect(), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_mermaid() {
+        let mut graph = DirectedGraph::<&str, u32>::new();
+        let _ = graph.add_node("b", "backend");
+        let _ = graph.add_node("a", "frontend");
+        let _ = graph.add_node("my node", "backend");
+        let _ = graph.add_node("end", "");
+        let _ = graph.add_edge_with("a", "b", 1);
+        let _ = graph.add_edge_with("b", "my node", 2);
+        let _ = graph.add_edge_with("my node", "end", 3);
+
+        assert_eq!(
+            graph.to_mermaid(),
+            "flowchart TD\n    a\n    b\n    node_0[\"end\"]\n    node_1[\"my node\"]\n    a --> b\n    b --> node_1\n    node_1 --> node_0\n"
+        );
+
+        let options = MermaidOptions::<&str, u32>::new()
+            .direction(MermaidDirection::LeftRight)
+            .node_label(|node| Some(format!("{} \"{}\"", node.id(), node.data())))
+            .edge_label(|edge| (**edge.data() > 1).then(|| edge.data().to_string()))
+            .group_by(|node| (!node.data().is_empty()).then(|| node.data().to_string()));
+        assert_eq!(
+            graph.to_mermaid_with(options),
+            r#"flowchart LR
+    node_0["end #quot;#quot;"]
+    subgraph backend["backend"]
+        b["b #quot;backend#quot;"]
+        node_1["my node #quot;backend#quot;"]
+    end
+    subgraph frontend["frontend"]
+        a["a #quot;frontend#quot;"]
+    end
+    a --> b
+    b -->|"2"| node_1
+    node_1 -->|"3"| node_0
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_mermaid_generated_ids_do_not_collide() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("node_0", ());
+        let _ = graph.add_node("a-b", ());
+        let _ = graph.add_edge("a-b", "node_0");
+
+        assert_eq!(
+            graph.to_mermaid(),
+            "flowchart TD\n    node_1[\"a-b\"]\n    node_0\n    node_1 --> node_0\n"
+        );
+    }
+
+    #[test]
+    fn test_dag_to_mermaid_is_topological() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("z", ());
+        let _ = graph.add_node("y", ());
+        let _ = graph.add_node("x", ());
+        let _ = graph.add_path(&["z", "y", "x"]);
+        let dag = DirectedAcyclicGraph::build(graph).unwrap();
+
+        assert_eq!(
+            dag.to_mermaid(),
+            "flowchart TD\n    z\n    y\n    x\n    z --> y\n    y --> x\n"
+        );
+    }
+}
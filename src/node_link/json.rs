@@ -0,0 +1,478 @@
+//! Minimal JSON value, reader and writer used by the node-link format
+use super::NodeLinkError;
+use std::collections::BTreeMap;
+
+/// JSON object with its keys sorted, so output is stable
+pub type JsonObject = BTreeMap<String, JsonValue>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    /// Number written without a fraction or an exponent that fits an
+    /// `i64`, kept exact since it is often an id
+    Integer(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(JsonObject),
+}
+
+impl JsonValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Integer(value) => Some(*value as f64),
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl std::fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out);
+        f.write_str(&out)
+    }
+}
+
+impl JsonValue {
+    /// Writes the value without any whitespace
+    pub(super) fn write(&self, out: &mut String) {
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Self::Integer(value) => out.push_str(&value.to_string()),
+            // JSON has no representation for NaN or infinity
+            Self::Number(value) if !value.is_finite() => out.push_str("null"),
+            Self::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                out.push_str(&format!("{}", *value as i64))
+            }
+            Self::Number(value) => out.push_str(&value.to_string()),
+            Self::String(value) => write_string(out, value),
+            Self::Array(values) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    value.write(out);
+                }
+                out.push(']');
+            }
+            Self::Object(object) => {
+                write_object(out, object.iter().map(|(key, value)| (key.as_str(), value)))
+            }
+        }
+    }
+}
+
+/// Writes an object with its entries in the given order
+pub(super) fn write_object<'a>(
+    out: &mut String,
+    entries: impl Iterator<Item = (&'a str, &'a JsonValue)>,
+) {
+    out.push('{');
+    for (i, (key, value)) in entries.enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_string(out, key);
+        out.push_str(": ");
+        value.write(out);
+    }
+    out.push('}');
+}
+
+/// How deeply arrays and objects may nest before the reader gives up,
+/// so hostile input cannot overflow the stack
+const MAX_DEPTH: usize = 128;
+
+pub(super) struct JsonReader<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    depth: usize,
+}
+
+impl<'a> JsonReader<'a> {
+    pub(super) fn new(input: &'a str) -> Self {
+        JsonReader {
+            chars: input.chars().peekable(),
+            line: 1,
+            depth: 0,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> NodeLinkError {
+        NodeLinkError::Parse {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), NodeLinkError> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("Expected `{expected}`, found `{c}`"))),
+            None => Err(self.error(format!("Expected `{expected}`, found end of input"))),
+        }
+    }
+
+    /// Reads a whole document, which must hold a single value
+    pub(super) fn read_document(mut self) -> Result<JsonValue, NodeLinkError> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        match self.next() {
+            None => Ok(value),
+            Some(c) => Err(self.error(format!("Unexpected `{c}` after the document"))),
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, NodeLinkError> {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some(c @ ('{' | '[')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error(format!("Nested deeper than {MAX_DEPTH} levels")));
+                }
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.string().map(JsonValue::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some(c) if c.is_alphabetic() => {
+                let mut word = String::new();
+                while let Some(c) = self.chars.peek().copied().filter(|c| c.is_alphanumeric()) {
+                    word.push(c);
+                    self.next();
+                }
+                match word.as_str() {
+                    "null" => Ok(JsonValue::Null),
+                    "true" => Ok(JsonValue::Bool(true)),
+                    "false" => Ok(JsonValue::Bool(false)),
+                    _ => Err(self.error(format!("Unexpected `{word}`"))),
+                }
+            }
+            Some(c) => Err(self.error(format!("Unexpected `{c}`"))),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, NodeLinkError> {
+        self.expect('{')?;
+        let mut object = JsonObject::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.next();
+            return Ok(JsonValue::Object(object));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() != Some(&'"') {
+                return Err(self.error("Expected a string key"));
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            let value = self.value()?;
+            object.insert(key, value);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(JsonValue::Object(object)),
+                _ => return Err(self.error("Expected `,` or `}` in object")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, NodeLinkError> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.next();
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(JsonValue::Array(values)),
+                _ => return Err(self.error("Expected `,` or `]` in array")),
+            }
+        }
+    }
+
+    /// Moves the next character into `number` if it matches
+    fn take(&mut self, number: &mut String, matches: impl Fn(char) -> bool) -> bool {
+        match self.chars.peek().copied().filter(|&c| matches(c)) {
+            Some(c) => {
+                number.push(c);
+                self.next();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves at least one digit into `number`
+    fn digits(&mut self, number: &mut String) -> Result<(), NodeLinkError> {
+        if !self.take(number, |c| c.is_ascii_digit()) {
+            return Err(self.error(format!("Invalid number `{number}`")));
+        }
+        while self.take(number, |c| c.is_ascii_digit()) {}
+        Ok(())
+    }
+
+    /// Reads a number following the JSON grammar: an optional minus, an
+    /// integer part without leading zeros, then optional fraction and
+    /// exponent parts that each need at least one digit
+    fn number(&mut self) -> Result<JsonValue, NodeLinkError> {
+        let mut number = String::new();
+        self.take(&mut number, |c| c == '-');
+        if !self.take(&mut number, |c| c == '0') {
+            self.digits(&mut number)?;
+        }
+        let mut integer = true;
+        if self.take(&mut number, |c| c == '.') {
+            integer = false;
+            self.digits(&mut number)?;
+        }
+        if self.take(&mut number, |c| matches!(c, 'e' | 'E')) {
+            integer = false;
+            self.take(&mut number, |c| matches!(c, '+' | '-'));
+            self.digits(&mut number)?;
+        }
+        if self.take(&mut number, |c| c.is_ascii_digit() || c == '.') {
+            return Err(self.error(format!("Invalid number `{number}`")));
+        }
+        if integer {
+            if let Ok(value) = number.parse() {
+                return Ok(JsonValue::Integer(value));
+            }
+        }
+        number
+            .parse()
+            .map(JsonValue::Number)
+            .map_err(|_| self.error(format!("Invalid number `{number}`")))
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, NodeLinkError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("Invalid unicode escape"))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, NodeLinkError> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => return Ok(value),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let mut code = self.hex_escape()?;
+                            if (0xd800..0xdc00).contains(&code) {
+                                if self.next() != Some('\\') || self.next() != Some('u') {
+                                    return Err(self.error("Unpaired surrogate"));
+                                }
+                                let low = self.hex_escape()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(self.error("Unpaired surrogate"));
+                                }
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                            }
+                            char::from_u32(code)
+                                .ok_or_else(|| self.error("Invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("Invalid escape")),
+                    };
+                    value.push(c);
+                }
+                Some(c) => value.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(input: &str) -> Result<JsonValue, NodeLinkError> {
+        JsonReader::new(input).read_document()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let input = r#"{"a": [1, -2.5, true, null], "b": {"c": "line\nbreak \"quoted\" é 😀"}}"#;
+        let value = read(input).unwrap();
+
+        let JsonValue::Object(object) = &value else {
+            panic!("Expected an object");
+        };
+        assert_eq!(
+            object["b"],
+            JsonValue::Object(
+                [(
+                    "c".to_string(),
+                    JsonValue::from("line\nbreak \"quoted\" é 😀")
+                )]
+                .into()
+            )
+        );
+        assert_eq!(read(&value.to_string()).unwrap(), value);
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let line = |input: &str| match read(input) {
+            Err(NodeLinkError::Parse { line, .. }) => line,
+            _ => panic!("Expected a parse error"),
+        };
+
+        assert_eq!(line("{\n\"a\": 1,\n}"), 3);
+        assert_eq!(line("[1, 2"), 1);
+        assert_eq!(line("\"abc"), 1);
+        assert_eq!(line("nope"), 1);
+        assert_eq!(line("{} {}"), 1);
+    }
+
+    #[test]
+    fn test_json_numbers() {
+        assert_eq!(read("0").unwrap(), JsonValue::Integer(0));
+        assert_eq!(read("-12").unwrap(), JsonValue::Integer(-12));
+        assert_eq!(
+            read("9007199254740993").unwrap(),
+            JsonValue::Integer(9007199254740993)
+        );
+        assert_eq!(
+            read("99999999999999999999").unwrap(),
+            JsonValue::Number(1e20)
+        );
+        for (input, expected) in [
+            ("0.0", 0.0),
+            ("-0.5", -0.5),
+            ("12e3", 12e3),
+            ("1.5E-2", 1.5e-2),
+        ] {
+            assert_eq!(read(input).unwrap(), JsonValue::Number(expected));
+        }
+        for input in ["+1", "1.", "01", ".5", "-", "1e", "1e+", "1.2.3", "--1"] {
+            assert!(read(input).is_err(), "`{input}` should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_json_depth_limit() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(read(&nested(MAX_DEPTH)).is_ok());
+        assert!(matches!(
+            read(&nested(MAX_DEPTH + 1)),
+            Err(NodeLinkError::Parse { .. })
+        ));
+        // Deep enough to overflow the stack without the limit
+        assert!(read(&"[{\"a\":".repeat(1_000_000)).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+//! Node-link JSON format, compatible with `networkx.node_link_data`
+//! and the d3 force layout.
+//!
+//! ```json
+//! {
+//!   "directed": true,
+//!   "multigraph": false,
+//!   "graph": {},
+//!   "nodes": [
+//!     {"id": "a", "label": "First"},
+//!     {"id": "b"}
+//!   ],
+//!   "links": [
+//!     {"source": "a", "target": "b", "weight": 2}
+//!   ]
+//! }
+//! ```
+//!
+//! Node and edge data is stored as extra members of every node and
+//! link object through the [`NodeLinkData`] trait. The members `id`,
+//! `source` and `target` are reserved.
+use crate::prelude::*;
+
+mod json;
+use json::{write_object, JsonReader};
+pub use json::{JsonObject, JsonValue};
+
+/// Maps node or edge data to members of a node-link JSON object and
+/// back
+pub trait NodeLinkData: Sized {
+    fn to_json(&self) -> JsonObject;
+    /// Builds the data from every member of the object except the
+    /// reserved ones
+    fn from_json(object: JsonObject) -> Result<Self, String>;
+}
+
+impl NodeLinkData for () {
+    fn to_json(&self) -> JsonObject {
+        JsonObject::new()
+    }
+    fn from_json(_object: JsonObject) -> Result<Self, String> {
+        Ok(())
+    }
+}
+
+impl NodeLinkData for JsonObject {
+    fn to_json(&self) -> JsonObject {
+        self.clone()
+    }
+    fn from_json(object: JsonObject) -> Result<Self, String> {
+        Ok(object)
+    }
+}
+
+#[derive(Debug)]
+pub enum NodeLinkError {
+    /// The input is not valid JSON
+    Parse { line: usize, message: String },
+    /// The input is valid JSON but not a node-link graph we can read
+    Invalid(String),
+    /// [`NodeLinkData::from_json`] rejected the data of a node or link
+    Data { id: String, message: String },
+}
+
+impl std::fmt::Display for NodeLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse { line, message } => {
+                write!(f, "Unable to parse JSON, line {line}: {message}")
+            }
+            Self::Invalid(message) => write!(f, "Invalid node-link graph: {message}"),
+            Self::Data { id, message } => write!(f, "Invalid data for `{id}`: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for NodeLinkError {}
+
+const RESERVED: [&str; 3] = ["id", "source", "target"];
+
+/// Writes a node or link with the reserved members first
+fn write_element(out: &mut String, reserved: &[(&str, JsonValue)], data: JsonObject) {
+    let entries = reserved.iter().map(|(key, value)| (*key, value)).chain(
+        data.iter()
+            .filter(|(key, _)| !RESERVED.contains(&key.as_str()))
+            .map(|(key, value)| (key.as_str(), value)),
+    );
+    out.push_str("    ");
+    write_object(out, entries);
+}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData>
+where
+    Data: NodeLinkData,
+    EdgeData: NodeLinkData,
+{
+    /// Writes the graph as node-link JSON with one node or link per
+    /// line, sorted by id
+    pub fn to_node_link_json(&self) -> String {
+        let mut nodes = self.ids.ids().iter().zip(&self.data).collect::<Vec<_>>();
+        nodes.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        let mut edges = self.edges().collect::<Vec<_>>();
+        edges.sort_unstable_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+
+        let mut out = String::new();
+        out.push_str("{\n");
+        out.push_str("  \"directed\": true,\n");
+        out.push_str("  \"multigraph\": false,\n");
+        out.push_str("  \"graph\": {},\n");
+        out.push_str("  \"nodes\": [");
+        for (i, (node_id, data)) in nodes.into_iter().enumerate() {
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            write_element(&mut out, &[("id", node_id.as_ref().into())], data.to_json());
+        }
+        out.push_str("\n  ],\n");
+        out.push_str("  \"links\": [");
+        for (i, edge) in edges.into_iter().enumerate() {
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            write_element(
+                &mut out,
+                &[
+                    ("source", edge.from.as_ref().into()),
+                    ("target", edge.to.as_ref().into()),
+                ],
+                edge.data().to_json(),
+            );
+        }
+        out.push_str("\n  ]\n}\n");
+        out
+    }
+
+    /// Reads a node-link JSON graph. Numeric ids, as written by
+    /// networkx for integer nodes, are converted to strings. Links may
+    /// reference nodes listed after them.
+    pub fn from_node_link_json(input: &str) -> Result<Self, OrbweaverError> {
+        let JsonValue::Object(mut document) = JsonReader::new(input).read_document()? else {
+            return Err(NodeLinkError::Invalid("Document must be an object".to_string()).into());
+        };
+        if document.get("directed").and_then(JsonValue::as_bool) == Some(false) {
+            return Err(
+                NodeLinkError::Invalid("Undirected graphs are not supported".to_string()).into(),
+            );
+        }
+
+        let mut graph = DirectedGraph::new();
+        for mut node in elements(&mut document, "nodes")? {
+            let id = take_id(&mut node, "id", "node")?;
+            let data = Data::from_json(node).map_err(|message| NodeLinkError::Data {
+                id: id.clone(),
+                message,
+            })?;
+            if graph.add_node(&id, data).is_err() {
+                return Err(NodeLinkError::Invalid(format!("Duplicate node `{id}`")).into());
+            }
+        }
+        for mut link in elements(&mut document, "links")? {
+            let source = take_id(&mut link, "source", "link")?;
+            let target = take_id(&mut link, "target", "link")?;
+            let id = format!("{source} -> {target}");
+            let data = EdgeData::from_json(link).map_err(|message| NodeLinkError::Data {
+                id: id.clone(),
+                message,
+            })?;
+            if graph.edge_exists(&source, &target) {
+                return Err(NodeLinkError::Invalid(format!("Duplicate link `{id}`")).into());
+            }
+            if graph.add_edge_with(&source, &target, data).is_err() {
+                return Err(NodeLinkError::Invalid(format!(
+                    "Link `{id}` references a node that does not exist"
+                ))
+                .into());
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+/// Takes the array `key` out of the document, checking that every
+/// element is an object. A missing array is treated as empty.
+fn elements(document: &mut JsonObject, key: &str) -> Result<Vec<JsonObject>, NodeLinkError> {
+    let values = match document.remove(key) {
+        None => return Ok(Vec::new()),
+        Some(JsonValue::Array(values)) => values,
+        Some(_) => return Err(NodeLinkError::Invalid(format!("`{key}` must be an array"))),
+    };
+    values
+        .into_iter()
+        .map(|value| match value {
+            JsonValue::Object(object) => Ok(object),
+            _ => Err(NodeLinkError::Invalid(format!(
+                "Every element of `{key}` must be an object"
+            ))),
+        })
+        .collect()
+}
+
+fn take_id(object: &mut JsonObject, key: &str, element: &str) -> Result<String, NodeLinkError> {
+    match object.remove(key) {
+        Some(JsonValue::String(id)) => Ok(id),
+        Some(JsonValue::Integer(id)) => Ok(id.to_string()),
+        Some(number @ JsonValue::Number(_)) => Ok(number.to_string()),
+        _ => Err(NodeLinkError::Invalid(format!(
+            "Every {element} needs a string or number `{key}`"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Weight(f64);
+
+    impl NodeLinkData for Weight {
+        fn to_json(&self) -> JsonObject {
+            [("weight".to_string(), self.0.into())].into()
+        }
+        fn from_json(object: JsonObject) -> Result<Self, String> {
+            object
+                .get("weight")
+                .and_then(JsonValue::as_f64)
+                .map(Weight)
+                .ok_or_else(|| "Missing weight".to_string())
+        }
+    }
+
+    #[test]
+    fn test_node_link_round_trip() {
+        let mut graph = DirectedGraph::<JsonObject, Weight>::new();
+        let _ = graph.add_node("a", [("label".to_string(), "First".into())].into());
+        let _ = graph.add_node("b", JsonObject::new());
+        let _ = graph.add_edge_with("a", "b", Weight(2.0));
+
+        let written = graph.to_node_link_json();
+        assert_eq!(
+            written,
+            r#"{
+  "directed": true,
+  "multigraph": false,
+  "graph": {},
+  "nodes": [
+    {"id": "a", "label": "First"},
+    {"id": "b"}
+  ],
+  "links": [
+    {"source": "a", "target": "b", "weight": 2}
+  ]
+}
+"#
+        );
+
+        let parsed = DirectedGraph::<JsonObject, Weight>::from_node_link_json(&written).unwrap();
+        assert_eq!(
+            parsed.get_node("a").unwrap().data()["label"],
+            "First".into()
+        );
+        assert_eq!(**parsed.get_edge("a", "b").unwrap().data(), Weight(2.0));
+        assert_eq!(parsed.to_node_link_json(), written);
+    }
+
+    #[test]
+    fn test_from_networkx() {
+        // Output of `json.dumps(nx.node_link_data(g))` for a DiGraph
+        // with integer nodes
+        let input = r#"{"directed": true, "multigraph": false, "graph": {"name": "g"},
+            "nodes": [{"color": "red", "id": 1}, {"id": 2}],
+            "links": [{"weight": 0.5, "source": 1, "target": 2}]}"#;
+
+        let graph = DirectedGraph::<JsonObject, Weight>::from_node_link_json(input).unwrap();
+
+        assert_eq!(graph.get_node("1").unwrap().data()["color"], "red".into());
+        assert_eq!(**graph.get_edge("1", "2").unwrap().data(), Weight(0.5));
+    }
+
+    #[test]
+    fn test_from_node_link_json_large_ids() {
+        // Above 2^53, where the id would change value as an f64
+        let input = r#"{"nodes": [{"id": 9007199254740993}, {"id": 9007199254740992}],
+            "links": [{"source": 9007199254740993, "target": 9007199254740992}]}"#;
+
+        let graph = DirectedGraph::<JsonObject>::from_node_link_json(input).unwrap();
+
+        assert_eq!(graph.n_nodes(), 2);
+        assert!(graph.edge_exists("9007199254740993", "9007199254740992"));
+    }
+
+    #[test]
+    fn test_from_node_link_json_errors() {
+        let read = |input: &str| DirectedGraph::<(), Weight>::from_node_link_json(input);
+
+        assert!(matches!(
+            read("[]"),
+            Err(OrbweaverError::NodeLink(NodeLinkError::Invalid(_)))
+        ));
+        assert!(matches!(
+            read(r#"{"directed": false}"#),
+            Err(OrbweaverError::NodeLink(NodeLinkError::Invalid(_)))
+        ));
+        assert!(matches!(
+            read(r#"{"nodes": [{"name": "a"}]}"#),
+            Err(OrbweaverError::NodeLink(NodeLinkError::Invalid(_)))
+        ));
+        assert!(matches!(
+            read(
+                r#"{"nodes": [{"id": "a"}], "links": [{"source": "a", "target": "b", "weight": 1}]}"#
+            ),
+            Err(OrbweaverError::NodeLink(NodeLinkError::Invalid(_)))
+        ));
+        assert!(matches!(
+            read(
+                r#"{"nodes": [{"id": "a"}, {"id": "b"}], "links": [{"source": "a", "target": "b"}]}"#
+            ),
+            Err(OrbweaverError::NodeLink(NodeLinkError::Data { .. }))
+        ));
+        assert!(matches!(
+            read("{\"nodes\": [\n{\"id\": }]}"),
+            Err(OrbweaverError::NodeLink(NodeLinkError::Parse {
+                line: 2,
+                ..
+            }))
+        ));
+    }
+}
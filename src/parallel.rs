@@ -0,0 +1,160 @@
+//! Parallel versions of the heavy algorithms, enabled with the
+//! `rayon` feature.
+//!
+//! Work runs on the global rayon thread pool. Every `par_` method
+//! returns exactly what its sequential counterpart returns, in the same
+//! order.
+use crate::algo::all_paths_dfs;
+use crate::directed::pagerank::pagerank_iterations;
+use crate::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+impl<Data: Sync, EdgeData: Sync> DirectedGraph<Data, EdgeData> {
+    /// Parallel version of [`DirectedGraph::get_leaves_under_each`]
+    pub fn par_get_leaves_under_each(
+        &self,
+        roots: &[impl AsRef<str> + Sync],
+    ) -> GraphInteractionResult<Vec<Vec<NodeId>>> {
+        roots
+            .par_iter()
+            .map(|root| self.get_leaves_under(&[root]))
+            .collect()
+    }
+
+    /// Parallel version of [`DirectedGraph::pagerank`]
+    pub fn par_pagerank(
+        &self,
+        damping: f64,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> HashMap<NodeId, f64> {
+        pagerank_iterations(
+            self,
+            damping,
+            tolerance,
+            max_iterations,
+            &|rank, n_nodes| (0..n_nodes as u32).into_par_iter().map(rank).collect(),
+        )
+    }
+
+    /// Parallel version of [`DirectedGraph::node_metrics`]
+    pub fn par_node_metrics<T: Send>(
+        &self,
+        metric: impl Fn(Node<&Data>) -> T + Sync,
+    ) -> Vec<(NodeId, T)> {
+        let mut nodes = (0..self.n_nodes() as u32).collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|&node| self.id_at(node));
+        nodes
+            .into_par_iter()
+            .map(|node| {
+                let node_id = self.id_at(node).clone();
+                (
+                    node_id.clone(),
+                    metric(Node::new(node_id, self.data_at(node))),
+                )
+            })
+            .collect()
+    }
+}
+
+impl<Data: Sync, EdgeData: Sync> DirectedAcyclicGraph<Data, EdgeData> {
+    /// Parallel version of [`DirectedAcyclicGraph::find_all_paths`],
+    /// every child of `from` is explored as its own task
+    pub fn par_find_all_paths(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<Vec<Vec<NodeId>>> {
+        let start_id = self.get_node_id(&from)?;
+        let goal_id = self.get_node_id(&to)?;
+        if start_id == goal_id {
+            return Ok(vec![vec![start_id]]);
+        }
+
+        let children = self.children(&start_id)?.collect::<Vec<_>>();
+        let paths = children.into_par_iter().map(|child| {
+            let mut all_paths = Vec::new();
+            let mut current_path = vec![start_id.clone()];
+            all_paths_dfs(
+                self,
+                (*child).clone(),
+                &goal_id,
+                &mut current_path,
+                &mut all_paths,
+            );
+            all_paths
+        });
+
+        Ok(paths.flatten_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Layered graph large enough to be split over several tasks
+    fn layered() -> DirectedGraph<usize> {
+        let mut graph = DirectedGraph::new();
+        for layer in 0..6 {
+            for i in 0..8 {
+                let _ = graph.add_node(format!("{layer}-{i}"), layer * 8 + i);
+            }
+        }
+        for layer in 0..5 {
+            for i in 0..8 {
+                for j in [i, (i + 3) % 8] {
+                    let _ = graph.add_edge(format!("{layer}-{i}"), format!("{}-{j}", layer + 1));
+                }
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn test_par_get_leaves_under_each() {
+        let graph = layered();
+        let roots = ["0-0", "2-5", "5-1", "3-3"];
+
+        assert_eq!(
+            graph.par_get_leaves_under_each(&roots).unwrap(),
+            graph.get_leaves_under_each(&roots).unwrap()
+        );
+        assert!(graph.par_get_leaves_under_each(&["0-0", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_par_pagerank() {
+        let mut graph = layered();
+        let _ = graph.add_edge("5-0", "0-0");
+
+        assert_eq!(
+            graph.par_pagerank(0.85, 1e-10, 100),
+            graph.pagerank(0.85, 1e-10, 100)
+        );
+    }
+
+    #[test]
+    fn test_par_node_metrics() {
+        let graph = layered();
+        let metric =
+            |node: Node<&usize>| graph.descendants(node.id()).unwrap().count() + **node.data();
+
+        assert_eq!(graph.par_node_metrics(metric), graph.node_metrics(metric));
+    }
+
+    #[test]
+    fn test_par_find_all_paths() {
+        let dag = DirectedAcyclicGraph::build(layered()).unwrap();
+
+        assert_eq!(
+            dag.par_find_all_paths("0-0", "5-1").unwrap(),
+            dag.find_all_paths("0-0", "5-1").unwrap()
+        );
+        assert_eq!(
+            dag.par_find_all_paths("0-0", "0-0").unwrap(),
+            dag.find_all_paths("0-0", "0-0").unwrap()
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Property test harness shared by both graph types. Random sequences of
+//! mutations are applied to a graph and to a model made of plain sets,
+//! checking after every step that both agree and that the graph passes
+//! its own validation.
+
+use crate::prelude::*;
+use proptest::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+const NODES: [&str; 6] = ["a", "b", "c", "d", "e", "f"];
+
+#[derive(Debug, Clone)]
+pub(crate) enum Mutation {
+    AddNode(&'static str),
+    AddEdge(&'static str, &'static str, u32),
+    AddEdgeWithPolicy(&'static str, &'static str, u32, OnDuplicateEdge),
+    RemoveEdge(&'static str, &'static str),
+    TryRemoveEdge(&'static str, &'static str),
+    RemoveNode(&'static str),
+    TryRemoveNode(&'static str),
+    ClearEdges,
+    /// Runs a traversal. The DAG also builds its reachability index so
+    /// the next change must drop it
+    Query(&'static str),
+}
+
+fn node() -> impl Strategy<Value = &'static str> {
+    proptest::sample::select(&NODES[..])
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    let policy = prop_oneof![
+        Just(OnDuplicateEdge::Replace),
+        Just(OnDuplicateEdge::Ignore),
+        Just(OnDuplicateEdge::Error),
+    ];
+    prop_oneof![
+        4 => node().prop_map(Mutation::AddNode),
+        6 => (node(), node(), any::<u32>()).prop_map(|(from, to, data)| {
+            Mutation::AddEdge(from, to, data)
+        }),
+        3 => (node(), node(), any::<u32>(), policy).prop_map(|(from, to, data, policy)| {
+            Mutation::AddEdgeWithPolicy(from, to, data, policy)
+        }),
+        2 => (node(), node()).prop_map(|(from, to)| Mutation::RemoveEdge(from, to)),
+        2 => (node(), node()).prop_map(|(from, to)| Mutation::TryRemoveEdge(from, to)),
+        1 => node().prop_map(Mutation::RemoveNode),
+        1 => node().prop_map(Mutation::TryRemoveNode),
+        1 => Just(Mutation::ClearEdges),
+        2 => node().prop_map(Mutation::Query),
+    ]
+}
+
+pub(crate) fn mutations() -> impl Strategy<Value = Vec<Mutation>> {
+    proptest::collection::vec(mutation(), 0..200)
+}
+
+/// Expected state of the graph after a sequence of mutations
+struct Model {
+    acyclic: bool,
+    nodes: BTreeSet<&'static str>,
+    edges: BTreeMap<(&'static str, &'static str), u32>,
+}
+
+impl Model {
+    fn reaches(&self, from: &str, to: &str) -> bool {
+        let mut visited = BTreeSet::from([from]);
+        let mut to_visit = vec![from];
+        while let Some(current) = to_visit.pop() {
+            if current == to {
+                return true;
+            }
+            for &(_, child) in self.edges.keys().filter(|(parent, _)| *parent == current) {
+                if visited.insert(child) {
+                    to_visit.push(child);
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether a new edge `from -> to` is accepted
+    fn accepts_edge(&self, from: &str, to: &str) -> bool {
+        self.nodes.contains(from)
+            && self.nodes.contains(to)
+            && !(self.acyclic && self.reaches(to, from))
+    }
+
+    fn remove_node(&mut self, node: &str) -> bool {
+        self.edges
+            .retain(|&(parent, child), _| parent != node && child != node);
+        self.nodes.remove(node)
+    }
+
+    /// Applies the mutation, returning whether the graph should succeed
+    fn apply(&mut self, mutation: &Mutation) -> bool {
+        match *mutation {
+            Mutation::AddNode(node) => self.nodes.insert(node),
+            Mutation::AddEdge(from, to, data) => {
+                let accepted = self.edges.contains_key(&(from, to)) || self.accepts_edge(from, to);
+                if accepted {
+                    self.edges.insert((from, to), data);
+                }
+                accepted
+            }
+            Mutation::AddEdgeWithPolicy(from, to, data, policy) => {
+                if let Some(existing) = self.edges.get_mut(&(from, to)) {
+                    match policy {
+                        OnDuplicateEdge::Replace => *existing = data,
+                        OnDuplicateEdge::Ignore => (),
+                        OnDuplicateEdge::Error => return false,
+                    }
+                    return true;
+                }
+                let accepted = self.accepts_edge(from, to);
+                if accepted {
+                    self.edges.insert((from, to), data);
+                }
+                accepted
+            }
+            Mutation::RemoveEdge(from, to) => {
+                self.edges.remove(&(from, to));
+                true
+            }
+            Mutation::TryRemoveEdge(from, to) => self.edges.remove(&(from, to)).is_some(),
+            Mutation::RemoveNode(node) => {
+                self.remove_node(node);
+                true
+            }
+            Mutation::TryRemoveNode(node) => self.remove_node(node),
+            Mutation::ClearEdges => {
+                self.edges.clear();
+                true
+            }
+            Mutation::Query(_) => true,
+        }
+    }
+}
+
+/// Graph type driven by [`check_against_model`]
+pub(crate) trait UnderTest {
+    /// Applies the mutation, returning whether it succeeded
+    fn apply(&mut self, mutation: &Mutation) -> bool;
+    fn validate(&self) -> GraphInteractionResult<()>;
+    fn graph(&self) -> &DirectedGraph<(), u32>;
+}
+
+impl UnderTest for DirectedGraph<(), u32> {
+    fn apply(&mut self, mutation: &Mutation) -> bool {
+        match *mutation {
+            Mutation::AddNode(node) => self.add_node(node, ()).is_ok(),
+            Mutation::AddEdge(from, to, data) => self.add_edge_with(from, to, data).is_ok(),
+            Mutation::AddEdgeWithPolicy(from, to, data, policy) => {
+                self.add_edge_with_policy(from, to, data, policy).is_ok()
+            }
+            Mutation::RemoveEdge(from, to) => {
+                self.remove_edge(from, to);
+                true
+            }
+            Mutation::TryRemoveEdge(from, to) => self.try_remove_edge(from, to).is_ok(),
+            Mutation::RemoveNode(node) => {
+                self.remove_node(node);
+                true
+            }
+            Mutation::TryRemoveNode(node) => self.try_remove_node(node).is_ok(),
+            Mutation::ClearEdges => {
+                self.clear_edges();
+                true
+            }
+            Mutation::Query(node) => {
+                let _ = self.get_leaves_under(&[node]);
+                true
+            }
+        }
+    }
+    fn validate(&self) -> GraphInteractionResult<()> {
+        DirectedGraph::validate(self)
+    }
+    fn graph(&self) -> &DirectedGraph<(), u32> {
+        self
+    }
+}
+
+impl UnderTest for DirectedAcyclicGraph<(), u32> {
+    fn apply(&mut self, mutation: &Mutation) -> bool {
+        match *mutation {
+            Mutation::AddNode(node) => self.add_node(node, ()).is_ok(),
+            Mutation::AddEdge(from, to, data) => self.add_edge_with(from, to, data).is_ok(),
+            Mutation::AddEdgeWithPolicy(from, to, data, policy) => {
+                self.add_edge_with_policy(from, to, data, policy).is_ok()
+            }
+            Mutation::RemoveEdge(from, to) => {
+                self.remove_edge(from, to);
+                true
+            }
+            Mutation::TryRemoveEdge(from, to) => self.try_remove_edge(from, to).is_ok(),
+            Mutation::RemoveNode(node) => {
+                self.remove_node(node);
+                true
+            }
+            Mutation::TryRemoveNode(node) => self.try_remove_node(node).is_ok(),
+            Mutation::ClearEdges => {
+                let edges = self
+                    .edges()
+                    .map(|edge| (edge.from(), edge.to()))
+                    .collect::<Vec<_>>();
+                for (from, to) in edges {
+                    self.remove_edge(from, to);
+                }
+                true
+            }
+            Mutation::Query(node) => {
+                let _ = self.build_reachability_index();
+                let _ = self.get_leaves_under(&[node]);
+                true
+            }
+        }
+    }
+    fn validate(&self) -> GraphInteractionResult<()> {
+        DirectedAcyclicGraph::validate(self)
+    }
+    fn graph(&self) -> &DirectedGraph<(), u32> {
+        self
+    }
+}
+
+/// Applies `mutations` to `graph` and to a model, failing as soon as
+/// they disagree. `acyclic` makes the model reject edges closing a cycle.
+pub(crate) fn check_against_model(
+    mut graph: impl UnderTest,
+    acyclic: bool,
+    mutations: &[Mutation],
+) -> Result<(), TestCaseError> {
+    let mut model = Model {
+        acyclic,
+        nodes: BTreeSet::new(),
+        edges: BTreeMap::new(),
+    };
+    for (step, mutation) in mutations.iter().enumerate() {
+        prop_assert_eq!(
+            graph.apply(mutation),
+            model.apply(mutation),
+            "step {}: {:?}",
+            step,
+            mutation
+        );
+        if let Err(err) = graph.validate() {
+            return Err(TestCaseError::fail(format!(
+                "step {step}: {mutation:?}: {err}"
+            )));
+        }
+
+        let graph = graph.graph();
+        prop_assert_eq!(graph.n_nodes(), model.nodes.len());
+        prop_assert_eq!(graph.n_edges(), model.edges.len());
+        for (&(from, to), &data) in &model.edges {
+            let edge = graph.get_edge(from, to);
+            prop_assert_eq!(edge.map(|edge| **edge.data()).ok(), Some(data));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,629 @@
+//! Borrowed views over a graph that hide nodes or edges or reverse
+//! the edges without copying anything.
+//!
+//! Views implement the traits in [`crate::visit`] so they work with
+//! every algorithm in [`crate::algo`], can be stacked on top of each
+//! other and can be copied into an owned graph with
+//! [`GraphView::to_graph`].
+use crate::prelude::*;
+use crate::visit::{Direction, GraphBase, IntoNeighbors, IntoNodeIdentifiers, Visitable};
+
+/// Common operations of the views
+pub trait GraphView: IntoNeighbors + IntoNodeIdentifiers + Visitable + Sized {
+    /// Children of `node` that are part of the view
+    fn children(&self, node: impl AsRef<str>) -> GraphInteractionResult<Self::Neighbors<'_>> {
+        match self.contains_node(node.as_ref()) {
+            true => Ok(self.neighbors_directed(node.as_ref(), Direction::Outgoing)),
+            false => Err(OrbweaverError::node_not_exists(node)),
+        }
+    }
+
+    /// Parents of `node` that are part of the view
+    fn parents(&self, node: impl AsRef<str>) -> GraphInteractionResult<Self::Neighbors<'_>> {
+        match self.contains_node(node.as_ref()) {
+            true => Ok(self.neighbors_directed(node.as_ref(), Direction::Incoming)),
+            false => Err(OrbweaverError::node_not_exists(node)),
+        }
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = Node<&Self::Data>> {
+        self.node_identifiers().map(|node_id| {
+            let data = self.node_data(node_id).expect("Node must exist");
+            Node::new(node_id.clone(), data)
+        })
+    }
+
+    fn node_ids(&self) -> impl Iterator<Item = NodeId> {
+        self.node_identifiers().cloned()
+    }
+
+    /// See [`crate::algo::find_path`]
+    fn find_path(
+        &self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+    ) -> GraphInteractionResult<Option<Vec<NodeId>>> {
+        crate::algo::find_path(self, from, to)
+    }
+
+    /// See [`crate::algo::get_leaves`]
+    fn get_leaves(&self) -> Vec<NodeId> {
+        crate::algo::get_leaves(self)
+    }
+
+    /// See [`crate::algo::get_leaves_under`]
+    fn get_leaves_under(&self, nodes: &[impl AsRef<str>]) -> GraphInteractionResult<Vec<NodeId>> {
+        crate::algo::get_leaves_under(self, nodes)
+    }
+
+    /// See [`crate::algo::topological_sort`]
+    fn topological_sort(&self) -> GraphInteractionResult<Vec<NodeId>> {
+        crate::algo::topological_sort(self)
+    }
+
+    /// Hides the nodes for which `predicate` returns `false`
+    fn filter_nodes<F>(&self, predicate: F) -> NodeFiltered<'_, Self, F>
+    where
+        F: Fn(Node<&Self::Data>) -> bool,
+    {
+        NodeFiltered::new(self, predicate)
+    }
+
+    /// Hides the edges for which `predicate` returns `false`
+    fn filter_edges<F>(&self, predicate: F) -> EdgeFiltered<'_, Self, F>
+    where
+        F: Fn(Edge<&Self::EdgeData>) -> bool,
+    {
+        EdgeFiltered::new(self, predicate)
+    }
+
+    /// Turns every edge around, parents become children
+    fn reversed(&self) -> Reversed<'_, Self> {
+        Reversed::new(self)
+    }
+
+    /// Copies the nodes and edges of the view into a new graph
+    fn to_graph(&self) -> DirectedGraph<Self::Data, Self::EdgeData>
+    where
+        Self::Data: Clone,
+        Self::EdgeData: Clone,
+    {
+        let mut graph = DirectedGraph::new();
+        for node_id in self.node_identifiers() {
+            let data = self.node_data(node_id).expect("Node must exist");
+            let _ = graph.add_node(node_id, data.clone());
+        }
+        for from in self.node_identifiers() {
+            for to in self.neighbors(from) {
+                let data = self.edge_data(from, to).expect("Edge must exist");
+                let _ = graph.add_edge_with(from, to, data.clone());
+            }
+        }
+        graph
+    }
+}
+
+fn includes<G, F>(graph: &G, predicate: &F, node_id: &NodeId) -> bool
+where
+    G: GraphBase,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    graph
+        .node_data(node_id)
+        .is_some_and(|data| predicate(Node::new(node_id.clone(), data)))
+}
+
+/// View without the nodes rejected by a predicate, or any of their
+/// edges. Created by [`DirectedGraph::filter_nodes`].
+pub struct NodeFiltered<'a, G, F> {
+    graph: &'a G,
+    predicate: F,
+}
+
+impl<'a, G, F> NodeFiltered<'a, G, F>
+where
+    G: GraphBase,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    pub fn new(graph: &'a G, predicate: F) -> Self {
+        NodeFiltered { graph, predicate }
+    }
+}
+
+impl<G, F> GraphBase for NodeFiltered<'_, G, F>
+where
+    G: IntoNodeIdentifiers,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    type Data = G::Data;
+    type EdgeData = G::EdgeData;
+
+    fn node_id(&self, id: &str) -> Option<&NodeId> {
+        self.graph
+            .node_id(id)
+            .filter(|node_id| includes(self.graph, &self.predicate, node_id))
+    }
+
+    fn node_data(&self, id: &str) -> Option<&G::Data> {
+        self.node_id(id)
+            .and_then(|node_id| self.graph.node_data(node_id))
+    }
+
+    fn edge_data(&self, from: &str, to: &str) -> Option<&G::EdgeData> {
+        match self.contains_node(from) && self.contains_node(to) {
+            true => self.graph.edge_data(from, to),
+            false => None,
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        self.node_identifiers().count()
+    }
+}
+
+/// Node ids of a [`NodeFiltered`] view
+pub struct NodeFilteredIds<'a, G: IntoNodeIdentifiers + 'a, F> {
+    graph: &'a G,
+    predicate: &'a F,
+    inner: G::NodeIdentifiers<'a>,
+}
+
+impl<'a, G, F> Iterator for NodeFilteredIds<'a, G, F>
+where
+    G: IntoNodeIdentifiers,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    type Item = &'a NodeId;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (graph, predicate) = (self.graph, self.predicate);
+        self.inner
+            .by_ref()
+            .find(|node_id| includes(graph, predicate, node_id))
+    }
+}
+
+impl<G, F> IntoNodeIdentifiers for NodeFiltered<'_, G, F>
+where
+    G: IntoNodeIdentifiers,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    type NodeIdentifiers<'b>
+        = NodeFilteredIds<'b, G, F>
+    where
+        Self: 'b;
+
+    fn node_identifiers(&self) -> Self::NodeIdentifiers<'_> {
+        NodeFilteredIds {
+            graph: self.graph,
+            predicate: &self.predicate,
+            inner: self.graph.node_identifiers(),
+        }
+    }
+}
+
+/// Neighbors of a node in a [`NodeFiltered`] view
+pub struct NodeFilteredNeighbors<'a, G: IntoNeighbors + 'a, F> {
+    graph: &'a G,
+    predicate: &'a F,
+    inner: Option<G::Neighbors<'a>>,
+}
+
+impl<'a, G, F> Iterator for NodeFilteredNeighbors<'a, G, F>
+where
+    G: IntoNeighbors,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    type Item = &'a NodeId;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (graph, predicate) = (self.graph, self.predicate);
+        self.inner
+            .as_mut()?
+            .find(|node_id| includes(graph, predicate, node_id))
+    }
+}
+
+impl<G, F> IntoNeighbors for NodeFiltered<'_, G, F>
+where
+    G: IntoNeighbors + IntoNodeIdentifiers,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    type Neighbors<'b>
+        = NodeFilteredNeighbors<'b, G, F>
+    where
+        Self: 'b;
+
+    fn neighbors_directed(&self, id: &str, direction: Direction) -> Self::Neighbors<'_> {
+        // Hidden nodes have no neighbors
+        let inner = self
+            .contains_node(id)
+            .then(|| self.graph.neighbors_directed(id, direction));
+        NodeFilteredNeighbors {
+            graph: self.graph,
+            predicate: &self.predicate,
+            inner,
+        }
+    }
+}
+
+impl<G, F> Visitable for NodeFiltered<'_, G, F>
+where
+    G: Visitable + IntoNodeIdentifiers,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+    type Map<'b>
+        = G::Map<'b>
+    where
+        Self: 'b;
+
+    fn visit_map(&self) -> Self::Map<'_> {
+        self.graph.visit_map()
+    }
+}
+
+impl<G, F> GraphView for NodeFiltered<'_, G, F>
+where
+    G: IntoNeighbors + IntoNodeIdentifiers + Visitable,
+    F: Fn(Node<&G::Data>) -> bool,
+{
+}
+
+/// View without the edges rejected by a predicate. Every node is kept.
+/// Created by [`DirectedGraph::filter_edges`].
+pub struct EdgeFiltered<'a, G, F> {
+    graph: &'a G,
+    predicate: F,
+}
+
+impl<'a, G, F> EdgeFiltered<'a, G, F>
+where
+    G: GraphBase,
+    F: Fn(Edge<&G::EdgeData>) -> bool,
+{
+    pub fn new(graph: &'a G, predicate: F) -> Self {
+        EdgeFiltered { graph, predicate }
+    }
+}
+
+impl<G, F> GraphBase for EdgeFiltered<'_, G, F>
+where
+    G: GraphBase,
+    F: Fn(Edge<&G::EdgeData>) -> bool,
+{
+    type Data = G::Data;
+    type EdgeData = G::EdgeData;
+
+    fn node_id(&self, id: &str) -> Option<&NodeId> {
+        self.graph.node_id(id)
+    }
+
+    fn node_data(&self, id: &str) -> Option<&G::Data> {
+        self.graph.node_data(id)
+    }
+
+    fn edge_data(&self, from: &str, to: &str) -> Option<&G::EdgeData> {
+        let from_id = self.graph.node_id(from)?;
+        let to_id = self.graph.node_id(to)?;
+        self.graph
+            .edge_data(from, to)
+            .filter(|&data| (self.predicate)(Edge::new(from_id.clone(), to_id.clone(), data)))
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+}
+
+impl<G, F> IntoNodeIdentifiers for EdgeFiltered<'_, G, F>
+where
+    G: IntoNodeIdentifiers,
+    F: Fn(Edge<&G::EdgeData>) -> bool,
+{
+    type NodeIdentifiers<'b>
+        = G::NodeIdentifiers<'b>
+    where
+        Self: 'b;
+
+    fn node_identifiers(&self) -> Self::NodeIdentifiers<'_> {
+        self.graph.node_identifiers()
+    }
+
+    fn node_index(&self, id: &NodeId) -> Option<usize> {
+        self.graph.node_index(id)
+    }
+}
+
+/// Neighbors of a node in an [`EdgeFiltered`] view
+pub struct EdgeFilteredNeighbors<'a, G: IntoNeighbors + 'a, F> {
+    graph: &'a G,
+    predicate: &'a F,
+    direction: Direction,
+    inner: Option<(&'a NodeId, G::Neighbors<'a>)>,
+}
+
+impl<'a, G, F> Iterator for EdgeFilteredNeighbors<'a, G, F>
+where
+    G: IntoNeighbors,
+    F: Fn(Edge<&G::EdgeData>) -> bool,
+{
+    type Item = &'a NodeId;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (graph, predicate, direction) = (self.graph, self.predicate, self.direction);
+        let (node_id, neighbors) = self.inner.as_mut()?;
+        let node_id: &NodeId = node_id;
+        neighbors.find(|&neighbor| {
+            let (from, to) = match direction {
+                Direction::Outgoing => (node_id, neighbor),
+                Direction::Incoming => (neighbor, node_id),
+            };
+            let data = graph.edge_data(from, to).expect("Edge must exist");
+            predicate(Edge::new(from.clone(), to.clone(), data))
+        })
+    }
+}
+
+impl<G, F> IntoNeighbors for EdgeFiltered<'_, G, F>
+where
+    G: IntoNeighbors,
+    F: Fn(Edge<&G::EdgeData>) -> bool,
+{
+    type Neighbors<'b>
+        = EdgeFilteredNeighbors<'b, G, F>
+    where
+        Self: 'b;
+
+    fn neighbors_directed(&self, id: &str, direction: Direction) -> Self::Neighbors<'_> {
+        let inner = self
+            .graph
+            .node_id(id)
+            .map(|node_id| (node_id, self.graph.neighbors_directed(id, direction)));
+        EdgeFilteredNeighbors {
+            graph: self.graph,
+            predicate: &self.predicate,
+            direction,
+            inner,
+        }
+    }
+}
+
+impl<G, F> Visitable for EdgeFiltered<'_, G, F>
+where
+    G: Visitable,
+    F: Fn(Edge<&G::EdgeData>) -> bool,
+{
+    type Map<'b>
+        = G::Map<'b>
+    where
+        Self: 'b;
+
+    fn visit_map(&self) -> Self::Map<'_> {
+        self.graph.visit_map()
+    }
+}
+
+impl<G, F> GraphView for EdgeFiltered<'_, G, F>
+where
+    G: IntoNeighbors + IntoNodeIdentifiers + Visitable,
+    F: Fn(Edge<&G::EdgeData>) -> bool,
+{
+}
+
+/// View with every edge turned around. Created by
+/// [`DirectedGraph::reversed`].
+pub struct Reversed<'a, G> {
+    graph: &'a G,
+}
+
+impl<'a, G: GraphBase> Reversed<'a, G> {
+    pub fn new(graph: &'a G) -> Self {
+        Reversed { graph }
+    }
+}
+
+impl<G: GraphBase> GraphBase for Reversed<'_, G> {
+    type Data = G::Data;
+    type EdgeData = G::EdgeData;
+
+    fn node_id(&self, id: &str) -> Option<&NodeId> {
+        self.graph.node_id(id)
+    }
+
+    fn node_data(&self, id: &str) -> Option<&G::Data> {
+        self.graph.node_data(id)
+    }
+
+    fn edge_data(&self, from: &str, to: &str) -> Option<&G::EdgeData> {
+        self.graph.edge_data(to, from)
+    }
+
+    fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+}
+
+impl<G: IntoNodeIdentifiers> IntoNodeIdentifiers for Reversed<'_, G> {
+    type NodeIdentifiers<'b>
+        = G::NodeIdentifiers<'b>
+    where
+        Self: 'b;
+
+    fn node_identifiers(&self) -> Self::NodeIdentifiers<'_> {
+        self.graph.node_identifiers()
+    }
+
+    fn node_index(&self, id: &NodeId) -> Option<usize> {
+        self.graph.node_index(id)
+    }
+}
+
+impl<G: IntoNeighbors> IntoNeighbors for Reversed<'_, G> {
+    type Neighbors<'b>
+        = G::Neighbors<'b>
+    where
+        Self: 'b;
+
+    fn neighbors_directed(&self, id: &str, direction: Direction) -> Self::Neighbors<'_> {
+        self.graph.neighbors_directed(id, direction.opposite())
+    }
+}
+
+impl<G: Visitable> Visitable for Reversed<'_, G> {
+    type Map<'b>
+        = G::Map<'b>
+    where
+        Self: 'b;
+
+    fn visit_map(&self) -> Self::Map<'_> {
+        self.graph.visit_map()
+    }
+}
+
+impl<G: IntoNeighbors + IntoNodeIdentifiers + Visitable> GraphView for Reversed<'_, G> {}
+
+impl<Data, EdgeData> DirectedGraph<Data, EdgeData> {
+    /// View hiding the nodes for which `predicate` returns `false`
+    /// together with their edges
+    pub fn filter_nodes<F>(&self, predicate: F) -> NodeFiltered<'_, Self, F>
+    where
+        F: Fn(Node<&Data>) -> bool,
+    {
+        NodeFiltered::new(self, predicate)
+    }
+
+    /// View hiding the edges for which `predicate` returns `false`
+    pub fn filter_edges<F>(&self, predicate: F) -> EdgeFiltered<'_, Self, F>
+    where
+        F: Fn(Edge<&EdgeData>) -> bool,
+    {
+        EdgeFiltered::new(self, predicate)
+    }
+
+    /// View with every edge turned around
+    pub fn reversed(&self) -> Reversed<'_, Self> {
+        Reversed::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Packages depending on each other, `true` if deprecated
+    fn packages() -> DirectedGraph<bool, u32> {
+        let mut graph = DirectedGraph::new();
+        let _ = graph.add_node("app", false);
+        let _ = graph.add_node("http", false);
+        let _ = graph.add_node("old-http", true);
+        let _ = graph.add_node("json", false);
+        let _ = graph.add_node("io", false);
+        let _ = graph.add_edge_with("app", "http", 2);
+        let _ = graph.add_edge_with("app", "old-http", 1);
+        let _ = graph.add_edge_with("old-http", "io", 1);
+        let _ = graph.add_edge_with("http", "json", 3);
+        let _ = graph.add_edge_with("json", "io", 1);
+        graph
+    }
+
+    fn sorted<'a>(nodes: impl Iterator<Item = &'a NodeId>) -> Vec<&'a NodeId> {
+        let mut nodes = nodes.collect::<Vec<_>>();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    #[test]
+    fn test_filter_nodes() {
+        let graph = packages();
+        let view = graph.filter_nodes(|node| !**node.data());
+
+        assert_eq!(view.node_count(), 4);
+        assert!(view.node_id("old-http").is_none());
+        assert_eq!(sorted(view.children("app").unwrap()), vec!["http"]);
+        assert_eq!(sorted(view.parents("io").unwrap()), vec!["json"]);
+        assert!(view.children("old-http").is_err());
+        assert_eq!(view.edge_data("old-http", "io"), None);
+        assert_eq!(view.nodes().filter(|node| **node.data()).count(), 0);
+        assert_eq!(
+            view.find_path("app", "io").unwrap(),
+            Some(vec![
+                "app".into(),
+                "http".into(),
+                "json".into(),
+                "io".into()
+            ])
+        );
+        assert_eq!(view.get_leaves(), vec!["io"]);
+    }
+
+    #[test]
+    fn test_filter_edges() {
+        let graph = packages();
+        let view = graph.filter_edges(|edge| **edge.data() > 1);
+
+        assert_eq!(view.node_count(), 5);
+        assert_eq!(sorted(view.children("app").unwrap()), vec!["http"]);
+        assert!(view.parents("io").unwrap().next().is_none());
+        assert_eq!(view.edge_data("http", "json"), Some(&3));
+        assert_eq!(view.edge_data("json", "io"), None);
+        assert_eq!(view.find_path("app", "io").unwrap(), None);
+        assert_eq!(view.get_leaves(), vec!["io", "json", "old-http"]);
+        assert_eq!(view.get_leaves_under(&["app"]).unwrap(), vec!["json"]);
+    }
+
+    #[test]
+    fn test_reversed() {
+        let graph = packages();
+        let view = graph.reversed();
+
+        assert_eq!(
+            sorted(view.children("io").unwrap()),
+            vec!["json", "old-http"]
+        );
+        assert_eq!(view.edge_data("http", "app"), Some(&2));
+        assert_eq!(view.edge_data("app", "http"), None);
+        assert_eq!(view.get_leaves(), vec!["app"]);
+
+        // Sinks of the reversed view come first, so every original
+        // edge goes from an earlier node to a later one
+        let sort = view.topological_sort().unwrap();
+        let position = |id: &NodeId| sort.iter().position(|node_id| node_id == id);
+        assert_eq!(sort.len(), 5);
+        for edge in graph.edges() {
+            assert!(position(&edge.from()) < position(&edge.to()));
+        }
+    }
+
+    #[test]
+    fn test_views_are_composable() {
+        let graph = packages();
+        let view = graph.filter_nodes(|node| !**node.data());
+        let reversed = view.reversed();
+        let direct = reversed.filter_edges(|edge| edge.to() != "json");
+
+        assert_eq!(sorted(reversed.children("io").unwrap()), vec!["json"]);
+        assert!(direct.children("io").unwrap().next().is_none());
+        assert_eq!(
+            crate::algo::get_leaves_under(&direct, &["json"]).unwrap(),
+            vec!["app"]
+        );
+    }
+
+    #[test]
+    fn test_view_to_graph() {
+        let mut graph = packages();
+        let _ = graph.add_edge_with("io", "app", 9);
+        let owned = graph
+            .filter_nodes(|node| !**node.data())
+            .filter_edges(|edge| edge.to() != "app")
+            .to_graph();
+
+        assert_eq!(owned.n_nodes(), 4);
+        assert_eq!(owned.edges().count(), 3);
+        assert_eq!(*owned.get_edge("http", "json").unwrap().data(), &3);
+        assert!(!owned.edge_exists("io", "app"));
+        assert!(DirectedAcyclicGraph::build(owned).is_ok());
+
+        let dag = DirectedAcyclicGraph::build(packages()).unwrap();
+        let reversed = dag.reversed().to_graph();
+        assert!(reversed.edge_exists("io", "json"));
+        assert!(reversed.get_leaves() == vec!["app"]);
+    }
+}
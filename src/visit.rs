@@ -0,0 +1,392 @@
+//! Traits describing the structure of a graph, so the algorithms in
+//! [`crate::algo`] work on [`DirectedGraph`], [`DirectedAcyclicGraph`],
+//! [`Graph`] and anything else exposing nodes and neighbors.
+use crate::directed::interner::Interner;
+use crate::directed::Neighbors;
+use crate::prelude::*;
+use std::collections::HashSet;
+
+/// Direction of the edges followed from a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// From parent to child
+    Outgoing,
+    /// From child to parent
+    Incoming,
+}
+
+impl Direction {
+    /// The other direction
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Outgoing => Direction::Incoming,
+            Direction::Incoming => Direction::Outgoing,
+        }
+    }
+}
+
+/// Nodes and edges of a graph together with their data
+pub trait GraphBase {
+    type Data;
+    type EdgeData;
+
+    /// Id stored in the graph for `id`, `None` if the node does not
+    /// exist
+    fn node_id(&self, id: &str) -> Option<&NodeId>;
+
+    /// Data of the node, `None` if the node does not exist
+    fn node_data(&self, id: &str) -> Option<&Self::Data>;
+
+    /// Data of the edge, `None` if the edge does not exist
+    fn edge_data(&self, from: &str, to: &str) -> Option<&Self::EdgeData>;
+
+    fn node_count(&self) -> usize;
+
+    fn contains_node(&self, id: &str) -> bool {
+        self.node_id(id).is_some()
+    }
+
+    fn contains_edge(&self, from: &str, to: &str) -> bool {
+        self.edge_data(from, to).is_some()
+    }
+}
+
+/// Iteration over the ids of every node
+pub trait IntoNodeIdentifiers: GraphBase {
+    type NodeIdentifiers<'a>: Iterator<Item = &'a NodeId>
+    where
+        Self: 'a;
+
+    /// Every node of the graph, in no particular order
+    fn node_identifiers(&self) -> Self::NodeIdentifiers<'_>;
+
+    /// Position of `id` in [`IntoNodeIdentifiers::node_identifiers`],
+    /// for graphs that store their nodes densely. Lets algorithms keep
+    /// per node state in a `Vec`, the default `None` makes them hash
+    /// the ids instead.
+    fn node_index(&self, _id: &NodeId) -> Option<usize> {
+        None
+    }
+}
+
+/// Iteration over the nodes adjacent to a node
+pub trait IntoNeighbors: GraphBase {
+    type Neighbors<'a>: Iterator<Item = &'a NodeId>
+    where
+        Self: 'a;
+
+    /// Children of `id` if `direction` is [`Direction::Outgoing`],
+    /// parents otherwise. Empty if the node does not exist.
+    fn neighbors_directed(&self, id: &str, direction: Direction) -> Self::Neighbors<'_>;
+
+    /// Children of `id`
+    fn neighbors(&self, id: &str) -> Self::Neighbors<'_> {
+        self.neighbors_directed(id, Direction::Outgoing)
+    }
+}
+
+/// Set of nodes already visited by a traversal
+pub trait VisitMap {
+    /// Marks the node as visited, returns `true` the first time
+    fn visit(&mut self, id: &NodeId) -> bool;
+
+    fn is_visited(&self, id: &str) -> bool;
+}
+
+impl VisitMap for HashSet<NodeId> {
+    fn visit(&mut self, id: &NodeId) -> bool {
+        self.insert(id.clone())
+    }
+
+    fn is_visited(&self, id: &str) -> bool {
+        self.contains(id)
+    }
+}
+
+/// Visit map over the dense indices of a [`DirectedGraph`], a flag per
+/// node instead of a set of cloned ids. Ids borrowed from the graph
+/// are found from their address, without hashing them.
+pub struct IndexVisitMap<'a> {
+    ids: &'a Interner,
+    visited: Vec<bool>,
+}
+
+impl VisitMap for IndexVisitMap<'_> {
+    fn visit(&mut self, id: &NodeId) -> bool {
+        match self.ids.get_borrowed(id) {
+            Some(node) => !std::mem::replace(&mut self.visited[node as usize], true),
+            None => false,
+        }
+    }
+
+    fn is_visited(&self, id: &str) -> bool {
+        self.ids
+            .get(id)
+            .is_some_and(|node| self.visited[node as usize])
+    }
+}
+
+/// Graphs that can create a [`VisitMap`] for their nodes
+pub trait Visitable: GraphBase {
+    type Map<'a>: VisitMap
+    where
+        Self: 'a;
+
+    /// An empty map sized for the graph
+    fn visit_map(&self) -> Self::Map<'_>;
+}
+
+impl<Data, EdgeData> GraphBase for DirectedGraph<Data, EdgeData> {
+    type Data = Data;
+    type EdgeData = EdgeData;
+
+    fn node_id(&self, id: &str) -> Option<&NodeId> {
+        self.index_of(id).map(|node| self.id_at(node))
+    }
+
+    fn node_data(&self, id: &str) -> Option<&Data> {
+        self.index_of(id).map(|node| self.data_at(node))
+    }
+
+    fn edge_data(&self, from: &str, to: &str) -> Option<&EdgeData> {
+        self.edge_data_at(self.index_of(from)?, self.index_of(to)?)
+    }
+
+    fn node_count(&self) -> usize {
+        self.n_nodes()
+    }
+}
+
+impl<Data, EdgeData> IntoNodeIdentifiers for DirectedGraph<Data, EdgeData> {
+    type NodeIdentifiers<'a>
+        = std::slice::Iter<'a, NodeId>
+    where
+        Self: 'a;
+
+    fn node_identifiers(&self) -> Self::NodeIdentifiers<'_> {
+        self.ids.ids().iter()
+    }
+
+    fn node_index(&self, id: &NodeId) -> Option<usize> {
+        self.ids.get_borrowed(id).map(|node| node as usize)
+    }
+}
+
+impl<Data, EdgeData> IntoNeighbors for DirectedGraph<Data, EdgeData> {
+    type Neighbors<'a>
+        = Neighbors<'a>
+    where
+        Self: 'a;
+
+    fn neighbors_directed(&self, id: &str, direction: Direction) -> Self::Neighbors<'_> {
+        self.neighbors_of(id, direction)
+    }
+}
+
+impl<Data, EdgeData> Visitable for DirectedGraph<Data, EdgeData> {
+    type Map<'a>
+        = IndexVisitMap<'a>
+    where
+        Self: 'a;
+
+    fn visit_map(&self) -> Self::Map<'_> {
+        IndexVisitMap {
+            ids: &self.ids,
+            visited: vec![false; self.n_nodes()],
+        }
+    }
+}
+
+impl<Data, EdgeData> GraphBase for DirectedAcyclicGraph<Data, EdgeData> {
+    type Data = Data;
+    type EdgeData = EdgeData;
+
+    fn node_id(&self, id: &str) -> Option<&NodeId> {
+        self.dg.node_id(id)
+    }
+
+    fn node_data(&self, id: &str) -> Option<&Data> {
+        self.dg.node_data(id)
+    }
+
+    fn edge_data(&self, from: &str, to: &str) -> Option<&EdgeData> {
+        self.dg.edge_data(from, to)
+    }
+
+    fn node_count(&self) -> usize {
+        self.dg.node_count()
+    }
+}
+
+impl<Data, EdgeData> IntoNodeIdentifiers for DirectedAcyclicGraph<Data, EdgeData> {
+    type NodeIdentifiers<'a>
+        = <DirectedGraph<Data, EdgeData> as IntoNodeIdentifiers>::NodeIdentifiers<'a>
+    where
+        Self: 'a;
+
+    fn node_identifiers(&self) -> Self::NodeIdentifiers<'_> {
+        self.dg.node_identifiers()
+    }
+
+    fn node_index(&self, id: &NodeId) -> Option<usize> {
+        self.dg.node_index(id)
+    }
+}
+
+impl<Data, EdgeData> IntoNeighbors for DirectedAcyclicGraph<Data, EdgeData> {
+    type Neighbors<'a>
+        = <DirectedGraph<Data, EdgeData> as IntoNeighbors>::Neighbors<'a>
+    where
+        Self: 'a;
+
+    fn neighbors_directed(&self, id: &str, direction: Direction) -> Self::Neighbors<'_> {
+        self.dg.neighbors_directed(id, direction)
+    }
+}
+
+impl<Data, EdgeData> Visitable for DirectedAcyclicGraph<Data, EdgeData> {
+    type Map<'a>
+        = IndexVisitMap<'a>
+    where
+        Self: 'a;
+
+    fn visit_map(&self) -> Self::Map<'_> {
+        self.dg.visit_map()
+    }
+}
+
+impl<Data, EdgeData> Graph<Data, EdgeData> {
+    /// Both variants share the same underlying structure
+    fn structure(&self) -> &DirectedGraph<Data, EdgeData> {
+        match self {
+            Graph::Directed(dg) => dg,
+            Graph::DirectedAcyclic(dag) => &dag.dg,
+        }
+    }
+}
+
+impl<Data, EdgeData> GraphBase for Graph<Data, EdgeData> {
+    type Data = Data;
+    type EdgeData = EdgeData;
+
+    fn node_id(&self, id: &str) -> Option<&NodeId> {
+        self.structure().node_id(id)
+    }
+
+    fn node_data(&self, id: &str) -> Option<&Data> {
+        self.structure().node_data(id)
+    }
+
+    fn edge_data(&self, from: &str, to: &str) -> Option<&EdgeData> {
+        self.structure().edge_data(from, to)
+    }
+
+    fn node_count(&self) -> usize {
+        self.structure().node_count()
+    }
+}
+
+impl<Data, EdgeData> IntoNodeIdentifiers for Graph<Data, EdgeData> {
+    type NodeIdentifiers<'a>
+        = <DirectedGraph<Data, EdgeData> as IntoNodeIdentifiers>::NodeIdentifiers<'a>
+    where
+        Self: 'a;
+
+    fn node_identifiers(&self) -> Self::NodeIdentifiers<'_> {
+        self.structure().node_identifiers()
+    }
+
+    fn node_index(&self, id: &NodeId) -> Option<usize> {
+        self.structure().node_index(id)
+    }
+}
+
+impl<Data, EdgeData> IntoNeighbors for Graph<Data, EdgeData> {
+    type Neighbors<'a>
+        = <DirectedGraph<Data, EdgeData> as IntoNeighbors>::Neighbors<'a>
+    where
+        Self: 'a;
+
+    fn neighbors_directed(&self, id: &str, direction: Direction) -> Self::Neighbors<'_> {
+        self.structure().neighbors_directed(id, direction)
+    }
+}
+
+impl<Data, EdgeData> Visitable for Graph<Data, EdgeData> {
+    type Map<'a>
+        = IndexVisitMap<'a>
+    where
+        Self: 'a;
+
+    fn visit_map(&self) -> Self::Map<'_> {
+        self.structure().visit_map()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<'a>(nodes: impl Iterator<Item = &'a NodeId>) -> Vec<&'a NodeId> {
+        let mut nodes = nodes.collect::<Vec<_>>();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    #[test]
+    fn test_graph_base_is_shared_by_every_graph() {
+        let mut graph = DirectedGraph::<u32, &str>::new();
+        let _ = graph.add_node("a", 1);
+        let _ = graph.add_node("b", 2);
+        let _ = graph.add_node("c", 3);
+        let _ = graph.add_edge_with("a", "b", "ab");
+        let _ = graph.add_edge_with("a", "c", "ac");
+        let dag = DirectedAcyclicGraph::build(graph.clone()).unwrap();
+        let wrapped = Graph::from(graph.clone());
+
+        fn check<G>(graph: &G)
+        where
+            G: IntoNeighbors<Data = u32, EdgeData = &'static str> + IntoNodeIdentifiers,
+        {
+            assert_eq!(graph.node_count(), 3);
+            assert_eq!(graph.node_id("b").unwrap(), "b");
+            assert_eq!(graph.node_data("c"), Some(&3));
+            assert_eq!(graph.edge_data("a", "c"), Some(&"ac"));
+            assert!(graph.contains_edge("a", "b"));
+            assert!(!graph.contains_edge("b", "a"));
+            assert!(!graph.contains_node("d"));
+            assert_eq!(sorted(graph.node_identifiers()), vec!["a", "b", "c"]);
+            assert_eq!(sorted(graph.neighbors("a")), vec!["b", "c"]);
+            assert_eq!(
+                sorted(graph.neighbors_directed("c", Direction::Incoming)),
+                vec!["a"]
+            );
+            assert_eq!(graph.neighbors("d").count(), 0);
+        }
+
+        check(&graph);
+        check(&dag);
+        check(&wrapped);
+    }
+
+    #[test]
+    fn test_visit_map() {
+        let mut graph = DirectedGraph::<()>::new();
+        let _ = graph.add_node("a", ());
+        let node_id = graph.node_id("a").unwrap().clone();
+
+        let mut map = graph.visit_map();
+
+        assert!(!map.is_visited("a"));
+        assert!(map.visit(&node_id));
+        assert!(!map.visit(&node_id));
+        assert!(map.is_visited("a"));
+        assert!(!map.is_visited("b"));
+
+        let mut set = HashSet::new();
+        assert!(set.visit(&node_id));
+        assert!(!set.visit(&node_id));
+        assert!(set.is_visited("a"));
+    }
+}